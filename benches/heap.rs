@@ -0,0 +1,165 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: heap.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use radixheap::RadixHeap;
+use radixheap::dijkstra::dijkstra;
+use rand::Rng;
+
+const SIZES: [usize; 3] = [1000, 10000, 100000];
+
+fn random_keys(size: usize) -> Vec<u32> {
+	let mut rng = rand::thread_rng();
+	(0..size).map(|_| rng.gen()).collect()
+}
+
+fn bench_push(c: &mut Criterion) {
+	let mut group = c.benchmark_group("push");
+
+	for &size in &SIZES {
+		let keys = random_keys(size);
+
+		group.bench_with_input(BenchmarkId::new("RadixHeap", size), &keys, |b, keys| {
+			b.iter(|| {
+				let mut heap = RadixHeap::new();
+				for &key in keys { heap.push(key, key).unwrap(); }
+				heap
+			})
+		});
+
+		group.bench_with_input(BenchmarkId::new("BinaryHeap", size), &keys, |b, keys| {
+			b.iter(|| {
+				let mut heap = BinaryHeap::new();
+				for &key in keys { heap.push(Reverse((key, key))); }
+				heap
+			})
+		});
+	}
+
+	group.finish();
+}
+
+fn bench_pop(c: &mut Criterion) {
+	let mut group = c.benchmark_group("pop");
+
+	for &size in &SIZES {
+		let keys = random_keys(size);
+		let radix: RadixHeap<u32> = keys.iter().fold(RadixHeap::new(), |mut h, &k| { h.push(k, k).unwrap(); h });
+		let binary: BinaryHeap<Reverse<(u32, u32)>> = keys.iter().map(|&k| Reverse((k, k))).collect();
+
+		group.bench_with_input(BenchmarkId::new("RadixHeap", size), &radix, |b, heap| {
+			b.iter_batched(|| heap.clone(), |mut heap| { while let Some(item) = heap.pop() { black_box(item); } },
+			               BatchSize::LargeInput)
+		});
+
+		group.bench_with_input(BenchmarkId::new("BinaryHeap", size), &binary, |b, heap| {
+			b.iter_batched(|| heap.clone(), |mut heap| { while let Some(item) = heap.pop() { black_box(item); } },
+			               BatchSize::LargeInput)
+		});
+	}
+
+	group.finish();
+}
+
+fn binary_heap_dijkstra(graph: &[Vec<(usize, u32)>], source: usize) -> Vec<Option<u32>> {
+	let mut distances = vec![None; graph.len()];
+	let mut heap = BinaryHeap::new();
+	heap.push(Reverse((0u32, source)));
+
+	while let Some(Reverse((distance, node))) = heap.pop() {
+		if distances[node].is_some() { continue; }
+		distances[node] = Some(distance);
+
+		for &(next, weight) in &graph[node] {
+			if distances[next].is_none() { heap.push(Reverse((distance + weight, next))); }
+		}
+	}
+
+	distances
+}
+
+// sparse random graph with average degree 8
+fn random_graph(nodes: usize) -> Vec<Vec<(usize, u32)>> {
+	let mut rng = rand::thread_rng();
+	(0..nodes).map(|_| (0..8).map(|_| (rng.gen_range(0, nodes), rng.gen_range(1, 10000))).collect()).collect()
+}
+
+fn bench_dijkstra(c: &mut Criterion) {
+	let mut group = c.benchmark_group("dijkstra");
+
+	for &size in &SIZES {
+		let graph = random_graph(size);
+
+		group.bench_with_input(BenchmarkId::new("RadixHeap", size), &graph, |b, graph| {
+			b.iter(|| dijkstra(graph, 0))
+		});
+
+		group.bench_with_input(BenchmarkId::new("BinaryHeap", size), &graph, |b, graph| {
+			b.iter(|| binary_heap_dijkstra(graph, 0))
+		});
+	}
+
+	group.finish();
+}
+
+// hold model of a timer queue: every expired timer schedules a new one a
+// random delay after the current time
+fn bench_timers(c: &mut Criterion) {
+	let mut group = c.benchmark_group("timers");
+
+	for &size in &SIZES {
+		let delays: Vec<u32> = random_keys(size).into_iter().map(|d| d % 100000).collect();
+
+		group.bench_with_input(BenchmarkId::new("RadixHeap", size), &delays, |b, delays| {
+			b.iter(|| {
+				let mut heap = RadixHeap::new();
+				for (i, &delay) in delays.iter().enumerate() { heap.push(delay, i).unwrap(); }
+
+				for &delay in delays {
+					let (now, i) = heap.pop().unwrap();
+					heap.push(now + delay, i).unwrap();
+				}
+			})
+		});
+
+		group.bench_with_input(BenchmarkId::new("BinaryHeap", size), &delays, |b, delays| {
+			b.iter(|| {
+				let mut heap = BinaryHeap::new();
+				for (i, &delay) in delays.iter().enumerate() { heap.push(Reverse((delay, i))); }
+
+				for &delay in delays {
+					let Reverse((now, i)) = heap.pop().unwrap();
+					heap.push(Reverse((now + delay, i)));
+				}
+			})
+		});
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, bench_push, bench_pop, bench_dijkstra, bench_timers);
+criterion_main!(benches);
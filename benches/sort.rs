@@ -0,0 +1,50 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: sort.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use radixheap::sort::radix_heap_sort;
+use rand::Rng;
+
+fn bench_sort(c: &mut Criterion) {
+	let mut group = c.benchmark_group("sort");
+	let mut rng = rand::thread_rng();
+
+	for &size in &[100usize, 1000, 10000] {
+		let input: Vec<(u32, u64)> = (0..size).map(|i| (rng.gen(), i as u64)).collect();
+
+		group.bench_with_input(BenchmarkId::new("radix_heap_sort", size), &input, |b, input| {
+			b.iter_batched(|| input.clone(), |mut v| radix_heap_sort(&mut v), BatchSize::SmallInput)
+		});
+
+		group.bench_with_input(BenchmarkId::new("sort_unstable_by_key", size), &input, |b, input| {
+			b.iter_batched(|| input.clone(), |mut v| v.sort_unstable_by_key(|t| t.0),
+			               BatchSize::SmallInput)
+		});
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, bench_sort);
+criterion_main!(benches);
@@ -22,10 +22,10 @@
  * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
  */
 
-use radixheap::radixheap::RadixHeap;
+use radixheap::RadixHeap;
 
 fn main() {
-    let mut heap: RadixHeap<&str> = RadixHeap::new(Some(8));
+    let mut heap: RadixHeap<&str> = RadixHeap::with_capacity(264);
 
     heap.push(18, "of").unwrap();
     heap.push(93, "rust").unwrap();
@@ -34,10 +34,10 @@ fn main() {
     heap.push(13, "world").unwrap();
     heap.push(211, "development").unwrap();
 
-    assert_eq!(heap.length(), 6);
-    assert!(heap.capacity() >= heap.length());
+    assert_eq!(heap.len(), 6);
+    assert!(heap.capacity() >= heap.len());
     assert_eq!(heap.capacity(), 264);
-    assert!(!heap.empty());
+    assert!(!heap.is_empty());
     assert_eq!(heap.peek(), Some((1, "hello")));
     assert_eq!(heap.tuples().first(), Some(&(1, "hello")));
     assert_eq!(heap.keys(), vec![1, 7, 13, 18, 93, 211]);
@@ -48,14 +48,14 @@ fn main() {
     heap.pop();
     assert_eq!(heap.peek(), Some((7, "amazing")));
 
-    for _ in 0..(heap.length() - 2) {
+    for _ in 0..(heap.len() - 2) {
         heap.pop();
-        assert!(!heap.empty());
+        assert!(!heap.is_empty());
     }
 
     assert_eq!(heap.values().join::<&str>(" "), "rust development");
 
     heap.clear();
-    assert_eq!(heap.length(), 0);
-    assert!(heap.empty());
+    assert_eq!(heap.len(), 0);
+    assert!(heap.is_empty());
 }
@@ -0,0 +1,141 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: operations.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+#![no_main]
+
+use std::collections::{BTreeMap, VecDeque};
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use radixheap::RadixHeap;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+	// pushes a key relative to the current bound, small deltas produce ties
+	Push(u16),
+	// pushes an arbitrary key, which has to be rejected if below the bound
+	PushAny(u32),
+	Pop,
+	Peek,
+	Clear,
+	ClearShrink,
+	Clone
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+	fifo: bool,
+	capacity: u8,
+	ops: Vec<Op>
+}
+
+// reference model: the ids of all items pushed so far, grouped by key in
+// the order they were pushed
+#[derive(Default)]
+struct Oracle {
+	items: BTreeMap<u32, VecDeque<u64>>,
+	length: usize,
+	bound: u32
+}
+
+impl Oracle {
+	fn push(&mut self, key: u32, id: u64) {
+		self.items.entry(key).or_default().push_back(id);
+		self.length += 1;
+	}
+
+	fn min(&self) -> Option<u32> { self.items.keys().next().copied() }
+
+	// removes the item popped by the heap, which must carry the smallest key
+	fn remove(&mut self, key: u32, id: u64, fifo: bool) {
+		assert_eq!(Some(key), self.min(), "popped key is not the smallest one");
+
+		let ids = self.items.get_mut(&key).unwrap();
+		let position = ids.iter().position(|&i| i == id).expect("popped item was never pushed");
+		if fifo { assert_eq!(position, 0, "equal keys not popped in push order"); }
+
+		ids.remove(position);
+		if ids.is_empty() { self.items.remove(&key); }
+		self.length -= 1;
+		self.bound = key;
+	}
+}
+
+fuzz_target!(|input: Input| {
+	let mut heap: RadixHeap<u64> = RadixHeap::builder()
+		.capacity(input.capacity as usize).fifo_ties(input.fifo).build();
+	let mut oracle = Oracle::default();
+	let mut next: u64 = 0;
+
+	for op in input.ops {
+		match op {
+			Op::Push(delta) => {
+				let key = oracle.bound.saturating_add(delta as u32);
+				heap.push(key, next).unwrap();
+				oracle.push(key, next);
+				next += 1;
+			},
+			Op::PushAny(key) => {
+				if key < oracle.bound {
+					assert_eq!(heap.push(key, next), Err("key too small"));
+				} else {
+					heap.push(key, next).unwrap();
+					oracle.push(key, next);
+				}
+
+				next += 1;
+			},
+			Op::Pop => {
+				if let Some((key, id)) = heap.pop() {
+					oracle.remove(key, id, input.fifo);
+				} else { assert_eq!(oracle.length, 0, "heap ran empty too early"); }
+			},
+			Op::Peek => assert_eq!(heap.peek().map(|(k, _)| k), oracle.min()),
+			Op::Clear => {
+				heap.clear();
+				oracle.items.clear();
+				oracle.length = 0;
+			},
+			Op::ClearShrink => {
+				heap.clear_shrink();
+				oracle.items.clear();
+				oracle.length = 0;
+			},
+			Op::Clone => {
+				let copy = heap.clone();
+				assert_eq!(copy.sorted_tuples(), heap.sorted_tuples());
+				heap = copy;
+			}
+		}
+
+		// bookkeeping has to stay in sync after every single operation
+		assert_eq!(heap.len(), oracle.length);
+		assert_eq!(heap.is_empty(), oracle.length == 0);
+		assert_eq!(heap.bound(), oracle.bound);
+		if let Err(errors) = heap.validate() { panic!("invariants violated: {:?}", errors); }
+	}
+
+	// draining has to yield everything left in ascending key order
+	while let Some((key, id)) = heap.pop() { oracle.remove(key, id, input.fifo); }
+	assert_eq!(oracle.length, 0);
+});
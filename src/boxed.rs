@@ -0,0 +1,122 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: boxed.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use crate::RadixHeap;
+
+// radix heap for large values: each value is put behind a pointer once,
+// so restructuring moves pointers instead of whole values. "Arc" rather
+// than "Box" is used as buckets keep a copy of their priority element,
+// which is only a reference count increment this way; values therefore
+// need not implement "Clone" either.
+#[derive(Debug)]
+pub struct BoxedRadixHeap<V: Debug + Ord> {
+	heap: RadixHeap<Arc<V>>
+}
+
+impl<V: Debug + Ord> BoxedRadixHeap<V> {
+	pub fn new() -> BoxedRadixHeap<V> { BoxedRadixHeap { heap: RadixHeap::new() } }
+
+	pub fn with_capacity(capacity: usize) -> BoxedRadixHeap<V> {
+		BoxedRadixHeap { heap: RadixHeap::with_capacity(capacity) }
+	}
+
+	pub fn len(&self) -> usize { self.heap.len() }
+
+	pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+	pub fn bound(&self) -> u32 { self.heap.bound() }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { self.heap.push(key, Arc::new(val)) }
+
+	// the heap drops its copy of a priority element before handing it out,
+	// and clones of the heap copy the values, so the popped pointer is the
+	// only one left
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let (key, val) = self.heap.pop()?;
+		Some((key, Arc::try_unwrap(val).expect("popped value still shared")))
+	}
+
+	pub fn peek(&self) -> Option<(u32, &V)> {
+		Some((self.heap.peek_key()?, self.heap.peek_value()?.as_ref()))
+	}
+
+	pub fn clear(&mut self) { self.heap.clear() }
+}
+
+// copies the values instead of sharing them with the clone
+impl<V: Clone + Debug + Ord> Clone for BoxedRadixHeap<V> {
+	fn clone(&self) -> BoxedRadixHeap<V> {
+		let mut heap = self.heap.clone();
+		for val in heap.values_mut() { *val = Arc::new(V::clone(val)); }
+		BoxedRadixHeap { heap }
+	}
+}
+
+impl<V: Debug + Ord> Default for BoxedRadixHeap<V> {
+	fn default() -> BoxedRadixHeap<V> { BoxedRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	// large and deliberately not "Clone"
+	#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+	struct Frame([u64; 64]);
+
+	#[test]
+	fn test_boxed_heap() {
+		let mut heap = BoxedRadixHeap::new();
+		for key in [700, 3, 90, 3, 45, 12000] { heap.push(key, Frame([u64::from(key); 64])).unwrap(); }
+
+		assert_eq!(heap.peek().map(|(k, f)| (k, f.0[0])), Some((3, 3)));
+
+		let mut keys = Vec::new();
+		while let Some((key, frame)) = heap.pop() {
+			assert_eq!(frame.0[63], u64::from(key));
+			keys.push(key);
+		}
+
+		assert_eq!(keys, vec![3, 3, 45, 90, 700, 12000]);
+		assert_eq!(heap.push(1, Frame([0; 64])), Err("key too small"));
+
+		// equal keys and equal values must not leave a pointer shared
+		for i in 0..300u64 { heap.push(12000 + (i % 7) as u32 * 50, Frame([i % 3; 64])).unwrap(); }
+		assert_eq!(std::iter::from_fn(|| heap.pop()).count(), 300);
+	}
+
+	#[test]
+	fn test_clone() {
+		let mut heap = BoxedRadixHeap::new();
+		heap.push(5, vec![5u8; 100]).unwrap();
+		heap.push(9, vec![9u8; 100]).unwrap();
+
+		let mut copy = heap.clone();
+		assert_eq!(copy.pop(), Some((5, vec![5; 100])));
+		assert_eq!(heap.pop(), Some((5, vec![5; 100])));
+		assert_eq!((heap.pop(), copy.pop()), (Some((9, vec![9; 100])), Some((9, vec![9; 100]))));
+	}
+}
@@ -0,0 +1,136 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: bucket.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::cmp::Ordering;
+use std::slice::Iter;
+use crate::iter::BucketIter;
+
+#[derive(Clone, Debug)]
+pub struct Bucket<V: Ord> {
+	pub(crate) index: usize,
+	pub(crate) top: Option<(u32, V)>,
+	pub(crate) items: Vec<(u32, V)>
+}
+
+// read-only look into one bucket of a heap
+#[derive(Clone, Copy, Debug)]
+pub struct BucketView<'a, V: Ord> {
+	pub(crate) bucket: &'a Bucket<V>,
+	pub(crate) range: Option<(u32, u32)>
+}
+
+impl<V: Ord> Bucket<V> {
+	pub(crate) fn length(&self) -> usize { self.items.len() }
+	pub(crate) fn capacity(&self) -> usize { self.items.capacity() }
+	pub(crate) fn empty(&self) -> bool { self.items.is_empty() }
+	pub(crate) fn clear(&mut self) {
+		self.items.clear();
+		self.top = None
+	}
+	pub(crate) fn iter(&self) -> BucketIter<'_, V> { BucketIter { container: self, index: 0 } }
+}
+
+impl<V: Clone + Ord> Bucket<V> {
+	// "front" only matters for bucket 0, which then keeps its priority
+	// element and hands out the new item last
+	pub(crate) fn push(&mut self, key: u32, val: V, front: bool) -> Result<(), &'static str> {
+		if self.index == 0 && front && !self.empty() {
+			self.items.insert(0, (key, val));
+			return Ok(());
+		}
+
+		// push key/value pair into bucket
+		self.items.push((key, val.clone()));
+
+		// update priority element of bucket
+		if self.index == 0 { self.top = Some((key, val.clone())); } else {
+			if let Some((k, _)) = self.top {
+				if key < k { self.top = Some((key, val.clone())); }
+			} else { self.top = Some((key, val.clone())); }
+		}
+
+		Ok(())
+	}
+
+	// takes out an arbitrary item and chooses the priority element anew
+	pub(crate) fn remove(&mut self, position: usize) -> (u32, V) {
+		let item = self.items.remove(position);
+		self.reset_top();
+		item
+	}
+
+	pub(crate) fn reset_top(&mut self) {
+		self.top = if self.index == 0 { self.items.last().cloned() }
+			else { self.iter().min_by_key(|(k, _)| k).cloned() };
+	}
+
+	pub(crate) fn pop(&mut self) -> Option<(u32, V)> {
+		let top = self.top.take();
+
+		if let Some((k, v)) = &top {
+			if self.index == 0 {
+				// all keys in bucket 0 are equal and its priority element
+				// is always the most recently pushed one
+				self.items.pop();
+				self.top = self.items.last().cloned();
+			} else {
+				self.items.remove(self.iter().position(|t| {
+					t.0 == *k && (t.1).cmp(v) == Ordering::Equal
+				}).unwrap());
+
+				// the new priority element must not be the one just removed
+				self.top = self.iter().min_by_key(|(k, _)| k).cloned();
+			}
+		} else { assert!(self.empty()); }
+
+		top
+	}
+}
+
+impl<'a, V: Ord> BucketView<'a, V> {
+	pub fn index(&self) -> usize { self.bucket.index }
+
+	// keys the bucket can hold given the current bound of the heap; "None"
+	// if no key at all can end up in it
+	pub fn range(&self) -> Option<(u32, u32)> { self.range }
+
+	pub fn len(&self) -> usize { self.bucket.length() }
+
+	pub fn is_empty(&self) -> bool { self.bucket.empty() }
+
+	pub fn capacity(&self) -> usize { self.bucket.capacity() }
+
+	// priority element of the bucket
+	pub fn min(&self) -> Option<(u32, &'a V)> { self.bucket.top.as_ref().map(|(k, v)| (*k, v)) }
+
+	// items in storage order
+	pub fn items(&self) -> Iter<'a, (u32, V)> { self.bucket.items.iter() }
+}
+
+#[cfg(feature = "defmt")]
+impl<'a, V: Ord> defmt::Format for BucketView<'a, V> {
+	fn format(&self, f: defmt::Formatter) {
+		defmt::write!(f, "Bucket {{ index: {=usize}, len: {=usize}, range: {} }}", self.index(), self.len(), self.range);
+	}
+}
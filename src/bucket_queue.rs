@@ -0,0 +1,116 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: bucket_queue.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+// Dial's bucket queue: a circular array with one bucket per key, usable
+// whenever keys never exceed the last extracted key by more than "span",
+// e.g. in shortest path searches with maximum edge weight "span"
+#[derive(Clone, Debug)]
+pub struct BucketQueue<V> {
+	buckets: Vec<Vec<(u32, V)>>,
+	bound: u32,
+	length: usize
+}
+
+impl<V> BucketQueue<V> {
+	pub fn new(span: u32) -> BucketQueue<V> {
+		BucketQueue { buckets: (0..=span as usize).map(|_| Vec::new()).collect(), bound: 0, length: 0 }
+	}
+
+	pub fn span(&self) -> u32 { (self.buckets.len() - 1) as u32 }
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	// smallest key "push" currently accepts
+	pub fn bound(&self) -> u32 { self.bound }
+
+	fn slot(&self, key: u32) -> usize { key as usize % self.buckets.len() }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.bound { return Err("key too small"); }
+		if key - self.bound > self.span() { return Err("key out of range"); }
+
+		let slot = self.slot(key);
+		self.buckets[slot].push((key, val));
+		self.length += 1;
+		Ok(())
+	}
+
+	// position of the bucket holding the smallest key
+	fn first(&self) -> Option<usize> {
+		if self.is_empty() { return None; }
+
+		let start = self.slot(self.bound);
+		(0..self.buckets.len()).map(|i| (start + i) % self.buckets.len()).find(|&s| !self.buckets[s].is_empty())
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let slot = self.first()?;
+		let (key, val) = self.buckets[slot].pop()?;
+
+		self.bound = key;
+		self.length -= 1;
+		Some((key, val))
+	}
+
+	pub fn clear(&mut self) {
+		for bucket in &mut self.buckets { bucket.clear(); }
+		self.length = 0;
+	}
+}
+
+impl<V: Clone> BucketQueue<V> {
+	pub fn peek(&self) -> Option<(u32, V)> {
+		self.first().and_then(|s| self.buckets[s].last().cloned())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_bucket_queue() {
+		let mut queue = BucketQueue::new(10);
+		queue.push(7, 'h').unwrap();
+		queue.push(3, 'd').unwrap();
+		queue.push(10, 'k').unwrap();
+		assert_eq!(queue.push(11, 'l'), Err("key out of range"));
+
+		assert_eq!(queue.peek(), Some((3, 'd')));
+		assert_eq!(queue.pop(), Some((3, 'd')));
+		assert_eq!(queue.push(2, 'c'), Err("key too small"));
+
+		// the window moves along with the bound
+		queue.push(13, 'n').unwrap();
+		queue.push(7, 'x').unwrap();
+		assert_eq!(queue.pop(), Some((7, 'x')));
+		assert_eq!(queue.pop(), Some((7, 'h')));
+		assert_eq!(queue.pop(), Some((10, 'k')));
+		assert_eq!(queue.pop(), Some((13, 'n')));
+		assert_eq!(queue.pop(), None);
+		assert_eq!(queue.len(), 0);
+	}
+}
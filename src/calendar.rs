@@ -0,0 +1,175 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: calendar.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+const MIN_BUCKETS: usize = 2;
+
+// calendar queue: every bucket is one "day" of width "width", a "year" is
+// one round through all buckets; buckets are kept sorted in descending
+// order and the calendar is resized whenever it becomes too crowded or
+// too empty, estimating a new day width from the earliest events
+#[derive(Clone, Debug)]
+pub struct CalendarQueue<V> {
+	buckets: Vec<Vec<(u32, V)>>,
+	width: u64,
+	// bucket of the current day and the first key after that day
+	current: usize,
+	top: u64,
+	bound: u32,
+	length: usize
+}
+
+impl<V> CalendarQueue<V> {
+	pub fn new() -> CalendarQueue<V> {
+		CalendarQueue {
+			buckets: (0..MIN_BUCKETS).map(|_| Vec::new()).collect(),
+			width: 1,
+			current: 0,
+			top: 1,
+			bound: 0,
+			length: 0
+		}
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	// smallest key "push" currently accepts
+	pub fn bound(&self) -> u32 { self.bound }
+
+	fn slot(&self, key: u32) -> usize { (u64::from(key) / self.width) as usize % self.buckets.len() }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.bound { return Err("key too small"); }
+
+		self.insert(key, val);
+		self.length += 1;
+		if self.length > 2 * self.buckets.len() { self.resize(2 * self.buckets.len()); }
+		Ok(())
+	}
+
+	fn insert(&mut self, key: u32, val: V) {
+		let slot = self.slot(key);
+		let bucket = &mut self.buckets[slot];
+		let position = bucket.partition_point(|&(k, _)| k > key);
+		bucket.insert(position, (key, val));
+	}
+
+	// position of the bucket holding the smallest key and the end of the
+	// day that key falls into
+	fn first(&self) -> Option<(usize, u64)> {
+		if self.is_empty() { return None; }
+
+		let (mut current, mut top) = (self.current, self.top);
+		for _ in 0..self.buckets.len() {
+			if self.buckets[current].last().is_some_and(|&(k, _)| u64::from(k) < top) { return Some((current, top)); }
+
+			current = (current + 1) % self.buckets.len();
+			top += self.width;
+		}
+
+		// nothing within the next year, jump straight to the earliest event
+		self.buckets.iter().enumerate()
+			.filter_map(|(i, b)| b.last().map(|&(k, _)| (i, k)))
+			.min_by_key(|&(_, k)| k)
+			.map(|(i, k)| (i, (u64::from(k) / self.width + 1) * self.width))
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let (slot, top) = self.first()?;
+		let (key, val) = self.buckets[slot].pop()?;
+
+		self.current = slot;
+		self.top = top;
+		self.bound = key;
+		self.length -= 1;
+		if self.buckets.len() > MIN_BUCKETS && self.length < self.buckets.len() / 2 {
+			self.resize(self.buckets.len() / 2);
+		}
+
+		Some((key, val))
+	}
+
+	pub fn clear(&mut self) {
+		for bucket in &mut self.buckets { bucket.clear(); }
+		self.length = 0;
+	}
+
+	fn resize(&mut self, count: usize) {
+		let mut items: Vec<(u32, V)> = self.buckets.iter_mut().flat_map(|b| b.drain(..)).collect();
+		items.sort_by_key(|&(k, _)| k);
+
+		// three times the average distance between the earliest events
+		let sample = &items[..items.len().min(25)];
+		if sample.len() > 1 {
+			let spread = u64::from(sample[sample.len() - 1].0 - sample[0].0);
+			self.width = (3 * spread / (sample.len() as u64 - 1)).max(1);
+		}
+
+		self.buckets = (0..count).map(|_| Vec::new()).collect();
+		self.current = self.slot(self.bound);
+		self.top = (u64::from(self.bound) / self.width + 1) * self.width;
+
+		for (key, val) in items { self.insert(key, val); }
+	}
+}
+
+impl<V: Clone> CalendarQueue<V> {
+	pub fn peek(&self) -> Option<(u32, V)> {
+		let (slot, _) = self.first()?;
+		self.buckets[slot].last().cloned()
+	}
+}
+
+impl<V> Default for CalendarQueue<V> {
+	fn default() -> CalendarQueue<V> { CalendarQueue::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_calendar_queue() {
+		let mut queue = CalendarQueue::new();
+		let mut rng = rand::thread_rng();
+		let mut popped = Vec::new();
+
+		// simulation-like hold model: every extracted event schedules a new one
+		for i in 0..1000 { queue.push(rng.gen_range(0, 10000), i).unwrap(); }
+		assert!(queue.buckets.len() >= 512);
+
+		while let Some((time, i)) = queue.peek() {
+			assert_eq!(queue.pop(), Some((time, i)));
+			popped.push(time);
+			if i < 5000 { queue.push(time + rng.gen_range(0, 10000), i + 1000).unwrap(); }
+		}
+
+		assert_eq!(popped.len(), 6000);
+		assert!(popped.windows(2).all(|w| w[0] <= w[1]));
+		assert_eq!(queue.buckets.len(), MIN_BUCKETS);
+		assert_eq!(queue.push(popped[0], 0), Err("key too small"));
+	}
+}
@@ -0,0 +1,164 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: channel.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::sync::{Arc, Condvar, Mutex};
+use crate::RadixHeap;
+
+struct Shared<V: Clone + Debug + Ord> {
+	heap: RadixHeap<V>,
+	senders: usize,
+	receiver: bool
+}
+
+struct Channel<V: Clone + Debug + Ord> {
+	shared: Mutex<Shared<V>>,
+	available: Condvar
+}
+
+pub struct Sender<V: Clone + Debug + Ord> {
+	channel: Arc<Channel<V>>
+}
+
+pub struct Receiver<V: Clone + Debug + Ord> {
+	channel: Arc<Channel<V>>
+}
+
+// multi-producer single-consumer channel delivering the message with the
+// lowest priority key first; keys must not be lower than the one of the
+// message received last
+pub fn channel<V: Clone + Debug + Ord>() -> (Sender<V>, Receiver<V>) {
+	let channel = Arc::new(Channel {
+		shared: Mutex::new(Shared { heap: RadixHeap::new(), senders: 1, receiver: true }),
+		available: Condvar::new()
+	});
+
+	(Sender { channel: Arc::clone(&channel) }, Receiver { channel })
+}
+
+impl<V: Clone + Debug + Ord> Sender<V> {
+	pub fn send(&self, priority: u32, msg: V) -> Result<(), &'static str> {
+		let mut shared = self.channel.shared.lock().unwrap();
+
+		if !shared.receiver { return Err("receiver disconnected"); }
+		shared.heap.push(priority, msg).map_err(|_| "key too small")?;
+		self.channel.available.notify_one();
+
+		Ok(())
+	}
+}
+
+impl<V: Clone + Debug + Ord> Clone for Sender<V> {
+	fn clone(&self) -> Sender<V> {
+		self.channel.shared.lock().unwrap().senders += 1;
+		Sender { channel: Arc::clone(&self.channel) }
+	}
+}
+
+impl<V: Clone + Debug + Ord> Drop for Sender<V> {
+	fn drop(&mut self) {
+		let mut shared = self.channel.shared.lock().unwrap();
+		shared.senders -= 1;
+		if shared.senders == 0 { self.channel.available.notify_all(); }
+	}
+}
+
+impl<V: Clone + Debug + Ord> Receiver<V> {
+	// blocks until a message is available; "None" once the channel is empty
+	// and all senders are gone
+	pub fn recv(&self) -> Option<(u32, V)> {
+		let mut shared = self.channel.shared.lock().unwrap();
+
+		loop {
+			if let Some(msg) = shared.heap.pop() { return Some(msg); }
+			if shared.senders == 0 { return None; }
+			shared = self.channel.available.wait(shared).unwrap();
+		}
+	}
+
+	pub fn try_recv(&self) -> Option<(u32, V)> {
+		self.channel.shared.lock().unwrap().heap.pop()
+	}
+
+	pub fn len(&self) -> usize { self.channel.shared.lock().unwrap().heap.len() }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl<V: Clone + Debug + Ord> Drop for Receiver<V> {
+	fn drop(&mut self) {
+		self.channel.shared.lock().unwrap().receiver = false;
+	}
+}
+
+impl<V: Clone + Debug + Ord> Iterator for Receiver<V> {
+	type Item = (u32, V);
+
+	fn next(&mut self) -> Option<Self::Item> { self.recv() }
+}
+
+// both ends can be moved to other threads as long as the messages can
+#[allow(dead_code)]
+const _: () = {
+	fn send<T: Send>() {}
+	fn is_send<V: Clone + Debug + Ord + Send>() {
+		send::<Sender<V>>();
+		send::<Receiver<V>>();
+	}
+};
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::thread;
+
+	#[test]
+	fn test_channel() {
+		let (tx, rx) = channel();
+		let producers: Vec<_> = (0..4u32).map(|p| {
+			let tx = tx.clone();
+			thread::spawn(move || {
+				for i in 0..25u32 { tx.send(i * 4 + p, p).unwrap(); }
+			})
+		}).collect();
+
+		for producer in producers { producer.join().unwrap(); }
+		drop(tx);
+
+		let keys: Vec<u32> = rx.map(|(k, _)| k).collect();
+		assert_eq!(keys, (0..100).collect::<Vec<u32>>());
+	}
+
+	#[test]
+	fn test_disconnect() {
+		let (tx, rx) = channel();
+		tx.send(5, "five").unwrap();
+		assert_eq!(rx.try_recv(), Some((5, "five")));
+		assert_eq!(tx.send(4, "four"), Err("key too small"));
+		assert_eq!(rx.try_recv(), None);
+
+		drop(rx);
+		assert_eq!(tx.send(6, "six"), Err("receiver disconnected"));
+	}
+}
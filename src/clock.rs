@@ -0,0 +1,117 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: clock.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+// source of the current time, in ticks, for timers and pacers; must never
+// go backwards
+pub trait Clock: Debug {
+	fn now(&self) -> u32;
+}
+
+// monotonic wall clock counting ticks of "tick" length since its creation;
+// saturates at "u32::MAX" ticks
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+	origin: Instant,
+	tick: Duration
+}
+
+impl SystemClock {
+	pub fn new(tick: Duration) -> SystemClock {
+		assert!(!tick.is_zero(), "tick length must not be zero");
+		SystemClock { origin: Instant::now(), tick }
+	}
+}
+
+impl Clock for SystemClock {
+	fn now(&self) -> u32 {
+		let ticks = self.origin.elapsed().as_nanos() / self.tick.as_nanos();
+		ticks.min(u128::from(u32::MAX)) as u32
+	}
+}
+
+// clock moved by hand, e.g. by tests; clones share the same time
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock(Arc<AtomicU32>);
+
+impl ManualClock {
+	pub fn new() -> ManualClock { ManualClock::default() }
+
+	// times before the current one are ignored
+	pub fn set(&self, now: u32) { self.0.fetch_max(now, Ordering::SeqCst); }
+
+	pub fn advance(&self, ticks: u32) {
+		let _ = self.0.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |t| Some(t.saturating_add(ticks)));
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> u32 { self.0.load(Ordering::SeqCst) }
+}
+
+// simulated logical clock moving "step" ticks forward on every reading
+#[derive(Clone, Debug)]
+pub struct StepClock {
+	next: Cell<u32>,
+	step: u32
+}
+
+impl StepClock {
+	pub fn new(start: u32, step: u32) -> StepClock { StepClock { next: Cell::new(start), step } }
+}
+
+impl Clock for StepClock {
+	fn now(&self) -> u32 {
+		let now = self.next.get();
+		self.next.set(now.saturating_add(self.step));
+		now
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_clocks() {
+		let manual = ManualClock::new();
+		let shared = manual.clone();
+		shared.advance(5);
+		manual.set(3);
+		assert_eq!(manual.now(), 5);
+		shared.set(9);
+		assert_eq!(manual.now(), 9);
+
+		let step = StepClock::new(10, 4);
+		assert_eq!((step.now(), step.now(), step.now()), (10, 14, 18));
+
+		let system = SystemClock::new(Duration::from_secs(3600));
+		assert_eq!(system.now(), 0);
+	}
+}
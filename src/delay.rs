@@ -0,0 +1,196 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: delay.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crate::timer::{TimerId, TimerWheel};
+
+struct State {
+	wheel: TimerWheel<Waker>,
+	stopped: bool
+}
+
+struct Shared {
+	state: Mutex<State>,
+	changed: Condvar,
+	origin: Instant
+}
+
+impl Shared {
+	// deadlines are kept as milliseconds since the start of the service and
+	// rounded up, so that delays never end early
+	fn ticks(&self, when: Instant) -> u32 {
+		let elapsed = when.saturating_duration_since(self.origin).as_nanos();
+		elapsed.div_ceil(1_000_000).min(u128::from(u32::MAX)) as u32
+	}
+
+	fn now(&self) -> u32 { self.origin.elapsed().as_millis().min(u128::from(u32::MAX)) as u32 }
+}
+
+// single background thread sleeping until the earliest deadline of all
+// delays handed out, independent of any async runtime; delays still
+// pending when the service is dropped are never woken
+pub struct TimerService {
+	shared: Arc<Shared>,
+	thread: Option<JoinHandle<()>>
+}
+
+impl TimerService {
+	pub fn new() -> TimerService {
+		let state = State { wheel: TimerWheel::new(), stopped: false };
+		let shared = Arc::new(Shared { state: Mutex::new(state), changed: Condvar::new(), origin: Instant::now() });
+		let driver = Arc::clone(&shared);
+
+		TimerService { shared, thread: Some(thread::spawn(move || drive(&driver))) }
+	}
+
+	pub fn delay(&self, duration: Duration) -> Delay { self.delay_until(Instant::now() + duration) }
+
+	pub fn delay_until(&self, when: Instant) -> Delay {
+		Delay { shared: Arc::clone(&self.shared), when, id: None }
+	}
+
+	// number of delays currently waiting to be woken
+	pub fn pending(&self) -> usize { self.shared.state.lock().unwrap().wheel.len() }
+}
+
+impl Default for TimerService {
+	fn default() -> TimerService { TimerService::new() }
+}
+
+impl Drop for TimerService {
+	fn drop(&mut self) {
+		self.shared.state.lock().unwrap().stopped = true;
+		self.shared.changed.notify_one();
+		if let Some(thread) = self.thread.take() { let _ = thread.join(); }
+	}
+}
+
+fn drive(shared: &Shared) {
+	let mut state = shared.state.lock().unwrap();
+
+	while !state.stopped {
+		let expired = state.wheel.advance(shared.now());
+
+		// wake outside of the lock, the woken tasks may poll right away
+		if !expired.is_empty() {
+			drop(state);
+			for (_, waker) in expired { waker.wake(); }
+			state = shared.state.lock().unwrap();
+			continue;
+		}
+
+		state = match state.wheel.next_deadline() {
+			Some(deadline) => {
+				let when = shared.origin + Duration::from_millis(u64::from(deadline));
+				let timeout = when.saturating_duration_since(Instant::now());
+				shared.changed.wait_timeout(state, timeout).unwrap().0
+			},
+			None => shared.changed.wait(state).unwrap()
+		};
+	}
+}
+
+// future completing at "when", woken by the "TimerService" it came from
+pub struct Delay {
+	shared: Arc<Shared>,
+	when: Instant,
+	id: Option<TimerId>
+}
+
+impl Delay {
+	pub fn deadline(&self) -> Instant { self.when }
+
+	fn cancel(&mut self) {
+		if let Some(id) = self.id.take() { self.shared.state.lock().unwrap().wheel.cancel(id); }
+	}
+}
+
+impl Future for Delay {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		let delay = self.get_mut();
+
+		if Instant::now() >= delay.when {
+			delay.cancel();
+			return Poll::Ready(());
+		}
+
+		// registered anew on every poll, as the task may have moved
+		let mut state = delay.shared.state.lock().unwrap();
+		if let Some(id) = delay.id.take() { state.wheel.cancel(id); }
+		delay.id = Some(state.wheel.insert(delay.shared.ticks(delay.when), cx.waker().clone()));
+
+		drop(state);
+		delay.shared.changed.notify_one();
+		Poll::Pending
+	}
+}
+
+impl Drop for Delay {
+	fn drop(&mut self) { self.cancel(); }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use futures::executor::block_on;
+	use futures::future::{join3, select, Either};
+
+	#[test]
+	fn test_delays() {
+		let service = TimerService::new();
+		let start = Instant::now();
+
+		let (a, b, c) = (service.delay(Duration::from_millis(30)), service.delay(Duration::from_millis(10)),
+		                 service.delay_until(start + Duration::from_millis(20)));
+		block_on(join3(a, b, c));
+
+		assert!(start.elapsed() >= Duration::from_millis(30));
+		assert_eq!(service.pending(), 0);
+	}
+
+	#[test]
+	fn test_dropped_delay() {
+		let service = TimerService::new();
+		let short = service.delay(Duration::from_millis(10));
+		let long = service.delay(Duration::from_secs(3600));
+
+		// the losing delay is dropped and gives up its registration
+		match block_on(select(short, long)) {
+			Either::Left((_, long)) => {
+				assert_eq!(service.pending(), 1);
+				drop(long);
+			},
+			Either::Right(_) => panic!("long delay finished first")
+		}
+
+		assert_eq!(service.pending(), 0);
+	}
+}
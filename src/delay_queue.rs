@@ -0,0 +1,203 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: delay_queue.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::VecDeque;
+use std::ops::Add;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use crate::timer::{TimerId, TimerWheel};
+
+// timer of an async runtime a "DelayQueue" sleeps on until its earliest
+// deadline
+pub trait Alarm: Sized {
+	type Instant: Copy + Add<Duration, Output = Self::Instant>;
+
+	fn now() -> Self::Instant;
+	fn at(when: Self::Instant) -> Self;
+	fn reset(&mut self, when: Self::Instant);
+	fn poll_alarm(&mut self, cx: &mut Context<'_>) -> Poll<()>;
+	fn since(when: Self::Instant, origin: Self::Instant) -> Duration;
+}
+
+#[cfg(feature = "tokio")]
+pub struct TokioAlarm(std::pin::Pin<Box<tokio::time::Sleep>>);
+
+#[cfg(feature = "tokio")]
+impl Alarm for TokioAlarm {
+	type Instant = tokio::time::Instant;
+
+	fn now() -> Self::Instant { tokio::time::Instant::now() }
+	fn at(when: Self::Instant) -> TokioAlarm { TokioAlarm(Box::pin(tokio::time::sleep_until(when))) }
+	fn reset(&mut self, when: Self::Instant) { self.0.as_mut().reset(when) }
+	fn poll_alarm(&mut self, cx: &mut Context<'_>) -> Poll<()> { std::future::Future::poll(self.0.as_mut(), cx) }
+	fn since(when: Self::Instant, origin: Self::Instant) -> Duration { when.saturating_duration_since(origin) }
+}
+
+// runtime agnostic timer, as used by "async-std" and "smol"
+#[cfg(feature = "async-io")]
+pub struct AsyncIoAlarm(async_io::Timer);
+
+#[cfg(feature = "async-io")]
+impl Alarm for AsyncIoAlarm {
+	type Instant = std::time::Instant;
+
+	fn now() -> Self::Instant { std::time::Instant::now() }
+	fn at(when: Self::Instant) -> AsyncIoAlarm { AsyncIoAlarm(async_io::Timer::at(when)) }
+	fn reset(&mut self, when: Self::Instant) { self.0.set_at(when) }
+
+	fn poll_alarm(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+		std::future::Future::poll(std::pin::Pin::new(&mut self.0), cx).map(|_| ())
+	}
+
+	fn since(when: Self::Instant, origin: Self::Instant) -> Duration { when.saturating_duration_since(origin) }
+}
+
+#[cfg(feature = "tokio")]
+pub type DefaultAlarm = TokioAlarm;
+
+#[cfg(all(feature = "async-io", not(feature = "tokio")))]
+pub type DefaultAlarm = AsyncIoAlarm;
+
+// deadlines are kept as milliseconds since the creation of the queue, so
+// they saturate after roughly 49 days
+pub struct DelayQueue<T, A: Alarm = DefaultAlarm> {
+	wheel: TimerWheel<T>,
+	expired: VecDeque<(TimerId, T)>,
+	// single timer, always armed for the earliest deadline
+	alarm: A,
+	origin: A::Instant
+}
+
+impl<T> DelayQueue<T> {
+	pub fn new() -> DelayQueue<T> { DelayQueue::with_alarm() }
+}
+
+impl<T, A: Alarm> DelayQueue<T, A> {
+	// queue driven by the timer of another runtime, e.g.
+	// "DelayQueue::<T, AsyncIoAlarm>::with_alarm()"
+	pub fn with_alarm() -> DelayQueue<T, A> {
+		let origin = A::now();
+		DelayQueue { wheel: TimerWheel::new(), expired: VecDeque::new(), alarm: A::at(origin), origin }
+	}
+
+	pub fn len(&self) -> usize { self.wheel.len() + self.expired.len() }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	pub fn insert(&mut self, value: T, timeout: Duration) -> TimerId {
+		self.insert_at(value, A::now() + timeout)
+	}
+
+	pub fn insert_at(&mut self, value: T, when: A::Instant) -> TimerId {
+		// round up so that entries never expire early
+		let elapsed = A::since(when, self.origin).as_nanos();
+		let ticks = elapsed.div_ceil(1_000_000).min(u128::from(u32::MAX)) as u32;
+		self.wheel.insert(ticks, value)
+	}
+
+	pub fn remove(&mut self, id: TimerId) -> Option<T> {
+		if let Some(position) = self.expired.iter().position(|&(i, _)| i == id) {
+			return self.expired.remove(position).map(|(_, v)| v);
+		}
+
+		self.wheel.cancel(id)
+	}
+
+	// yields the next expired entry, "None" once the queue is empty
+	pub fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<Option<(TimerId, T)>> {
+		loop {
+			if let Some(entry) = self.expired.pop_front() { return Poll::Ready(Some(entry)); }
+			if self.wheel.is_empty() { return Poll::Ready(None); }
+
+			let now = self.ticks(A::now());
+			self.expired.extend(self.wheel.advance(now));
+			if !self.expired.is_empty() { continue; }
+
+			match self.wheel.next_deadline() {
+				Some(deadline) => {
+					self.alarm.reset(self.origin + Duration::from_millis(u64::from(deadline)));
+					if self.alarm.poll_alarm(cx).is_pending() { return Poll::Pending; }
+				},
+				None => return Poll::Ready(None)
+			}
+		}
+	}
+
+	fn ticks(&self, instant: A::Instant) -> u32 {
+		let elapsed = A::since(instant, self.origin).as_millis();
+		elapsed.min(u128::from(u32::MAX)) as u32
+	}
+}
+
+impl<T> Default for DelayQueue<T> {
+	fn default() -> DelayQueue<T> { DelayQueue::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::future::poll_fn;
+
+	#[cfg(feature = "tokio")]
+	#[tokio::test(start_paused = true)]
+	async fn test_delay_queue() {
+		let mut queue = DelayQueue::new();
+		let start = tokio::time::Instant::now();
+
+		queue.insert("late", Duration::from_millis(300));
+		queue.insert("early", Duration::from_millis(100));
+		let cancelled = queue.insert("cancelled", Duration::from_millis(200));
+
+		assert_eq!(queue.remove(cancelled), Some("cancelled"));
+		assert_eq!(queue.len(), 2);
+
+		let (_, first) = poll_fn(|cx| queue.poll_expired(cx)).await.unwrap();
+		assert_eq!(first, "early");
+		assert!(start.elapsed() >= Duration::from_millis(100));
+
+		let (_, second) = poll_fn(|cx| queue.poll_expired(cx)).await.unwrap();
+		assert_eq!(second, "late");
+		assert!(start.elapsed() >= Duration::from_millis(300));
+
+		assert!(poll_fn(|cx| queue.poll_expired(cx)).await.is_none());
+	}
+
+	#[cfg(feature = "async-io")]
+	#[test]
+	fn test_async_io() {
+		let mut queue: DelayQueue<u32, AsyncIoAlarm> = DelayQueue::with_alarm();
+		let start = std::time::Instant::now();
+		queue.insert(2, Duration::from_millis(40));
+		queue.insert(1, Duration::from_millis(20));
+
+		let expired: Vec<u32> = futures::executor::block_on(async {
+			let mut expired = Vec::new();
+			while let Some((_, v)) = poll_fn(|cx| queue.poll_expired(cx)).await { expired.push(v); }
+			expired
+		});
+
+		assert_eq!(expired, vec![1, 2]);
+		assert!(start.elapsed() >= Duration::from_millis(40));
+	}
+}
@@ -0,0 +1,106 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: delta_stepping.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use crate::RadixHeap;
+
+// parallel single source shortest paths by delta-stepping: nodes are kept
+// in buckets of "delta" distance units, the radix heap holding them by
+// bucket number. The edges of all nodes of a bucket are relaxed in
+// parallel, light edges (at most "delta") repeatedly until the bucket
+// stays empty, heavy ones once afterwards. Small deltas approach Dijkstra,
+// large ones Bellman-Ford with more parallelism.
+pub fn delta_stepping(graph: &[Vec<(usize, u32)>], source: usize, delta: u32) -> Vec<Option<u32>> {
+	assert!(delta > 0, "delta must not be zero");
+
+	let mut dist: Vec<u32> = vec![u32::MAX; graph.len()];
+	let mut buckets: RadixHeap<usize> = RadixHeap::new();
+	dist[source] = 0;
+	buckets.push(0, source).unwrap();
+
+	while let Some(bucket) = buckets.peek_key() {
+		let mut settled: Vec<usize> = Vec::new();
+
+		loop {
+			// entries of nodes improved into a lower bucket are stale
+			let mut frontier: Vec<usize> = buckets.pop_while(|b, _| b == bucket).map(|(_, node)| node)
+				.filter(|&node| dist[node] / delta == bucket)
+				.collect();
+			if frontier.is_empty() { break; }
+
+			frontier.sort_unstable();
+			frontier.dedup();
+			let requests = requests(graph, &dist, &frontier, |w| w <= delta);
+			relax(&mut dist, &mut buckets, delta, requests);
+			settled.extend(frontier);
+		}
+
+		settled.sort_unstable();
+		settled.dedup();
+		let requests = requests(graph, &dist, &settled, |w| w > delta);
+		relax(&mut dist, &mut buckets, delta, requests);
+	}
+
+	dist.into_iter().map(|d| if d == u32::MAX { None } else { Some(d) }).collect()
+}
+
+// tentative distances over the selected edges of "nodes", computed in
+// parallel
+fn requests<F: Fn(u32) -> bool + Sync>(graph: &[Vec<(usize, u32)>], dist: &[u32], nodes: &[usize], select: F)
+	-> Vec<(usize, u32)> {
+	nodes.par_iter().flat_map_iter(|&node| {
+		let select = &select;
+		graph[node].iter().filter(move |&&(_, w)| select(w))
+			.filter_map(move |&(next, w)| dist[node].checked_add(w).map(|d| (next, d)))
+	}).collect()
+}
+
+fn relax(dist: &mut [u32], buckets: &mut RadixHeap<usize>, delta: u32, requests: Vec<(usize, u32)>) {
+	for (node, d) in requests {
+		if d < dist[node] {
+			dist[node] = d;
+			// never below the bucket being processed
+			buckets.push(d / delta, node).unwrap();
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::dijkstra::dijkstra;
+	use rand::Rng;
+
+	#[test]
+	fn test_delta_stepping() {
+		let mut rng = rand::thread_rng();
+		let graph: Vec<Vec<(usize, u32)>> = (0..500).map(|_| {
+			let degree = rng.gen_range(0, 8);
+			(0..degree).map(|_| (rng.gen_range(0, 500), rng.gen_range(1, 100))).collect()
+		}).collect();
+
+		let expected = dijkstra(&graph, 0);
+		for &delta in &[1, 7, 50, 1000] { assert_eq!(delta_stepping(&graph, 0, delta), expected); }
+	}
+}
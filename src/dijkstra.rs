@@ -0,0 +1,261 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: dijkstra.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use crate::graph::Graph;
+use crate::RadixHeap;
+use crate::tombstone::{TombstoneHandle, TombstoneHeap};
+
+// graphs are given as adjacency lists, where "graph[u]" holds all
+// "(v, weight)" pairs of the edges leaving node "u", or as any other
+// "Graph" such as "Csr"
+
+// how improved distances of nodes already in the heap are handled:
+// "LazyInsert" pushes another entry and skips the stale one on pop, which
+// is cheapest on sparse graphs, where few nodes are improved repeatedly.
+// "DecreaseKey" removes the old entry through its handle in a
+// "TombstoneHeap", which deletes lazily: the entry stays behind as a
+// tombstone until the heap is compacted once tombstones outnumber the
+// live entries by more than 32. It pays a handle lookup on every
+// improvement, but keeps the heap within about twice the number of nodes
+// on dense graphs, where lazy insertion may push up to one entry per edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+	LazyInsert,
+	DecreaseKey
+}
+
+enum Frontier {
+	Lazy(RadixHeap<(usize, usize)>),
+	Handles(TombstoneHeap<(usize, usize)>, Vec<Option<TombstoneHandle>>)
+}
+
+impl Frontier {
+	fn new(strategy: Strategy, nodes: usize) -> Frontier {
+		match strategy {
+			Strategy::LazyInsert => Frontier::Lazy(RadixHeap::new()),
+			Strategy::DecreaseKey => Frontier::Handles(TombstoneHeap::new(), vec![None; nodes])
+		}
+	}
+
+	// keys never drop below the last extracted one here
+	fn push(&mut self, key: u32, node: usize, origin: usize) {
+		match self {
+			Frontier::Lazy(heap) => heap.push(key, (node, origin)).unwrap(),
+			Frontier::Handles(heap, handles) => {
+				if let Some(old) = handles[node].take() { heap.remove(old); }
+				handles[node] = Some(heap.push(key, (node, origin)).unwrap());
+			}
+		}
+	}
+
+	// pops the next entry unless its key exceeds "bound"
+	fn pop_le(&mut self, bound: u32) -> Option<(u32, (usize, usize))> {
+		match self {
+			Frontier::Lazy(heap) => heap.pop_while(|k, _| k <= bound).next(),
+			Frontier::Handles(heap, _) => {
+				if heap.peek()?.0 > bound { None } else { heap.pop() }
+			}
+		}
+	}
+}
+
+pub fn dijkstra<G: Graph + ?Sized>(graph: &G, source: usize) -> Vec<Option<u32>> {
+	multi_source_dijkstra(&[source], graph).into_iter()
+		.map(|d| d.map(|(dist, _)| dist))
+		.collect()
+}
+
+// every node is mapped to its distance from the nearest source and that
+// source itself; unreachable nodes are mapped to "None"
+pub fn multi_source_dijkstra<G: Graph + ?Sized>(sources: &[usize], graph: &G)
+	-> Vec<Option<(u32, usize)>> {
+	multi_source_dijkstra_with(sources, graph, Strategy::LazyInsert)
+}
+
+pub fn multi_source_dijkstra_with<G: Graph + ?Sized>(sources: &[usize], graph: &G, strategy: Strategy)
+	-> Vec<Option<(u32, usize)>> {
+	search(sources, graph, strategy, None, u32::MAX).0
+}
+
+// distances of all nodes at most "bound" away from the source; the search
+// stops at the first node beyond it
+pub fn dijkstra_within<G: Graph + ?Sized>(graph: &G, source: usize, bound: u32) -> Vec<Option<u32>> {
+	search(&[source], graph, Strategy::LazyInsert, None, bound).0.into_iter()
+		.map(|d| d.map(|(dist, _)| dist))
+		.collect()
+}
+
+// distance and nodes of a shortest path from "source" to "target"; the
+// search stops as soon as the target is settled
+pub fn shortest_path<G: Graph + ?Sized>(graph: &G, source: usize, target: usize) -> Option<(u32, Vec<usize>)> {
+	let paths = paths(search(&[source], graph, Strategy::LazyInsert, Some(target), u32::MAX));
+	Some((paths.distance(target)?, paths.reconstruct_path(target)?))
+}
+
+// marks sources and unreachable nodes in a predecessor array
+pub const NO_PREDECESSOR: usize = usize::MAX;
+
+// distances from a source together with the predecessor of every reached
+// node on a shortest path to it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShortestPaths {
+	dist: Vec<Option<u32>>,
+	pred: Vec<usize>
+}
+
+impl ShortestPaths {
+	pub fn distance(&self, node: usize) -> Option<u32> { self.dist.get(node).copied().flatten() }
+
+	pub fn predecessor(&self, node: usize) -> Option<usize> {
+		self.pred.get(node).copied().filter(|&p| p != NO_PREDECESSOR)
+	}
+
+	// one entry per node, "NO_PREDECESSOR" for the source and unreachable
+	// nodes
+	pub fn predecessors(&self) -> &[usize] { &self.pred }
+
+	// nodes of a shortest path from the source to "target", both included
+	pub fn reconstruct_path(&self, target: usize) -> Option<Vec<usize>> {
+		self.distance(target)?;
+
+		let mut path = vec![target];
+		while let Some(pred) = self.predecessor(*path.last().unwrap()) { path.push(pred); }
+
+		path.reverse();
+		Some(path)
+	}
+}
+
+pub fn shortest_paths<G: Graph + ?Sized>(graph: &G, source: usize) -> ShortestPaths {
+	paths(search(&[source], graph, Strategy::LazyInsert, None, u32::MAX))
+}
+
+fn paths((dist, pred): (Vec<Option<(u32, usize)>>, Vec<usize>)) -> ShortestPaths {
+	ShortestPaths { dist: dist.into_iter().map(|d| d.map(|(dist, _)| dist)).collect(), pred }
+}
+
+// settles nodes up to distance "bound", or until "target" is settled
+fn search<G: Graph + ?Sized>(sources: &[usize], graph: &G, strategy: Strategy, target: Option<usize>, bound: u32)
+	-> (Vec<Option<(u32, usize)>>, Vec<usize>) {
+	let mut dist: Vec<Option<(u32, usize)>> = vec![None; graph.node_count()];
+	let mut best: Vec<u32> = vec![u32::MAX; graph.node_count()];
+	// predecessor belonging to the distance in "best"
+	let mut pred: Vec<usize> = vec![NO_PREDECESSOR; graph.node_count()];
+	let mut heap = Frontier::new(strategy, graph.node_count());
+
+	for &source in sources {
+		best[source] = 0;
+		heap.push(0, source, source);
+	}
+
+	while let Some((d, (node, origin))) = heap.pop_le(bound) {
+		// skip stale entries of already settled nodes
+		if dist[node].is_some() { continue; }
+		dist[node] = Some((d, origin));
+		if target == Some(node) { break; }
+
+		for (next, weight) in graph.edges(node) {
+			if dist[next].is_some() { continue; }
+
+			if let Some(nd) = d.checked_add(weight) {
+				if nd < best[next] {
+					best[next] = nd;
+					pred[next] = node;
+					heap.push(nd, next, origin);
+				}
+			}
+		}
+	}
+
+	(dist, pred)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn graph() -> Vec<Vec<(usize, u32)>> {
+		vec![
+			vec![(1, 4), (2, 1)],
+			vec![(3, 1)],
+			vec![(1, 2), (3, 5)],
+			vec![(4, 3)],
+			vec![],
+			vec![(4, 1)]
+		]
+	}
+
+	#[test]
+	fn test_dijkstra() {
+		assert_eq!(dijkstra(&graph(), 0),
+		           vec![Some(0), Some(3), Some(1), Some(4), Some(7), None]);
+	}
+
+	#[test]
+	fn test_multi_source() {
+		let dist = multi_source_dijkstra(&[0, 5], &graph());
+
+		assert_eq!(dist[0], Some((0, 0)));
+		assert_eq!(dist[5], Some((0, 5)));
+		assert_eq!(dist[3], Some((4, 0)));
+		assert_eq!(dist[4], Some((1, 5)));
+		assert_eq!(multi_source_dijkstra(&[], &graph()), vec![None; 6]);
+	}
+
+	#[test]
+	fn test_paths() {
+		let paths = shortest_paths(&graph(), 0);
+
+		assert_eq!(paths.reconstruct_path(4), Some(vec![0, 2, 1, 3, 4]));
+		assert_eq!(paths.reconstruct_path(0), Some(vec![0]));
+		assert_eq!(paths.reconstruct_path(5), None);
+		assert_eq!((paths.distance(3), paths.predecessor(3)), (Some(4), Some(1)));
+		assert_eq!(paths.predecessors()[..3], [NO_PREDECESSOR, 2, 0]);
+	}
+
+	#[test]
+	fn test_early_exit() {
+		assert_eq!(shortest_path(&graph(), 0, 3), Some((4, vec![0, 2, 1, 3])));
+		assert_eq!(shortest_path(&graph(), 0, 5), None);
+		assert_eq!(shortest_path(&graph(), 5, 5), Some((0, vec![5])));
+
+		assert_eq!(dijkstra_within(&graph(), 0, 3), vec![Some(0), Some(3), Some(1), None, None, None]);
+		assert_eq!(dijkstra_within(&graph(), 0, u32::MAX), dijkstra(&graph(), 0));
+	}
+
+	#[test]
+	fn test_strategies() {
+		// complete graph with weights making many relaxations improve
+		let dense: Vec<Vec<(usize, u32)>> = (0..40).map(|u| {
+			(0..40).filter(|&v| v != u).map(|v| (v, ((u * 7 + v * 13) % 50 + 1) as u32)).collect()
+		}).collect();
+
+		for sources in [vec![0], vec![3, 17]] {
+			assert_eq!(multi_source_dijkstra_with(&sources, &dense, Strategy::DecreaseKey),
+			           multi_source_dijkstra_with(&sources, &dense, Strategy::LazyInsert));
+		}
+
+		assert_eq!(multi_source_dijkstra_with(&[0], &graph(), Strategy::DecreaseKey)[4], Some((7, 0)));
+	}
+}
@@ -0,0 +1,218 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: embassy.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use core::cell::RefCell;
+use core::task::Waker;
+use critical_section::Mutex;
+use embassy_time_driver::Driver;
+use crate::static_heap::StaticRadixHeap;
+
+// hardware timer with one compare channel, counting ticks in 64 bits
+pub trait CompareChannel: Send + Sync + 'static {
+	fn now(&self) -> u64;
+	// fires the compare interrupt at "at"; fails if "at" has already passed
+	fn set_alarm(&self, at: u64) -> bool;
+}
+
+// software timers of at most "N" distinct wakers
+struct Queue<const N: usize> {
+	// keys are ticks since "epoch", values index "slots"
+	heap: StaticRadixHeap<usize, N>,
+	// deadline, waker, and key of the live heap entry of every timer
+	slots: [Option<(u64, Waker, u32)>; N],
+	epoch: u64
+}
+
+impl<const N: usize> Queue<N> {
+	const fn new() -> Queue<N> { Queue { heap: StaticRadixHeap::new(), slots: [const { None }; N], epoch: 0 } }
+
+	// fails if the timer cannot be queued, in which case its waker has to
+	// be woken right away
+	fn schedule(&mut self, at: u64, waker: &Waker, now: u64) -> Result<(), ()> {
+		let slot = match self.slots.iter().position(|s| s.as_ref().is_some_and(|(_, w, _)| w.will_wake(waker))) {
+			Some(slot) if self.slots[slot].as_ref().unwrap().0 <= at => return Ok(()),
+			Some(slot) => slot,
+			None => self.slots.iter().position(Option::is_none).ok_or(())?
+		};
+
+		// an empty heap starts over at the current time, so keys only have
+		// to cover the span of the pending timers; deadlines beyond that
+		// fire early and their tasks schedule them again
+		if self.heap.is_empty() { (self.heap, self.epoch) = (StaticRadixHeap::new(), now); }
+		let key = (at.saturating_sub(self.epoch).min(u32::MAX as u64) as u32).max(self.heap.bound());
+
+		// a timer moved forward leaves a stale entry, which is skipped once
+		// popped as its key no longer matches
+		if self.heap.is_full() { self.compact(slot); }
+		self.heap.push(key, slot).map_err(|_| ())?;
+		self.slots[slot] = Some((at, waker.clone(), key));
+		Ok(())
+	}
+
+	// drops the stale entries, and the entry of the timer "skip" about to
+	// be moved; the bound is kept, so the keys remain valid
+	fn compact(&mut self, skip: usize) {
+		self.heap.clear();
+		for (slot, entry) in self.slots.iter().enumerate() {
+			if let Some((_, _, key)) = entry.as_ref().filter(|_| slot != skip) { self.heap.push(*key, slot).unwrap(); }
+		}
+	}
+
+	fn next_deadline(&self) -> Option<u64> { self.heap.peek().map(|(k, _)| self.epoch + k as u64) }
+
+	fn pop_due(&mut self, now: u64) -> Option<Waker> {
+		while self.next_deadline()? <= now {
+			let (key, slot) = self.heap.pop()?;
+			if self.slots[slot].as_ref().is_some_and(|&(_, _, k)| k == key) {
+				return self.slots[slot].take().map(|(_, waker, _)| waker);
+			}
+		}
+
+		None
+	}
+
+	fn len(&self) -> usize { self.slots.iter().flatten().count() }
+}
+
+// "embassy-time" driver multiplexing up to "N" software timers over one
+// hardware compare channel; the compare interrupt has to call "on_alarm()"
+//
+//     embassy_time_driver::time_driver_impl!(
+//         static DRIVER: RadixTimeDriver<Rtc, 32> = RadixTimeDriver::new(Rtc::new()));
+pub struct RadixTimeDriver<C: CompareChannel, const N: usize> {
+	channel: C,
+	queue: Mutex<RefCell<Queue<N>>>
+}
+
+impl<C: CompareChannel, const N: usize> RadixTimeDriver<C, N> {
+	pub const fn new(channel: C) -> RadixTimeDriver<C, N> {
+		RadixTimeDriver { channel, queue: Mutex::new(RefCell::new(Queue::new())) }
+	}
+
+	pub fn channel(&self) -> &C { &self.channel }
+
+	// number of pending timers
+	pub fn pending(&self) -> usize { critical_section::with(|cs| self.queue.borrow_ref(cs).len()) }
+
+	// wakes all timers that are due and arms the channel for the next one
+	pub fn on_alarm(&self) {
+		critical_section::with(|cs| {
+			let mut queue = self.queue.borrow_ref_mut(cs);
+
+			loop {
+				while let Some(waker) = queue.pop_due(self.channel.now()) { waker.wake(); }
+				match queue.next_deadline() {
+					Some(at) if !self.channel.set_alarm(at) => continue,
+					_ => break
+				}
+			}
+		})
+	}
+}
+
+impl<C: CompareChannel, const N: usize> Driver for RadixTimeDriver<C, N> {
+	fn now(&self) -> u64 { self.channel.now() }
+
+	fn schedule_wake(&self, at: u64, waker: &Waker) {
+		let queued = critical_section::with(|cs| self.queue.borrow_ref_mut(cs).schedule(at, waker, self.channel.now()));
+		if queued.is_err() { waker.wake_by_ref(); } else { self.on_alarm(); }
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+	use std::sync::Arc;
+	use std::task::Wake;
+
+	#[derive(Default)]
+	struct FakeChannel { now: AtomicU64, alarm: AtomicU64 }
+
+	impl CompareChannel for FakeChannel {
+		fn now(&self) -> u64 { self.now.load(Ordering::SeqCst) }
+
+		fn set_alarm(&self, at: u64) -> bool {
+			self.alarm.store(at, Ordering::SeqCst);
+			at > self.now()
+		}
+	}
+
+	struct Counter(AtomicUsize);
+
+	impl Wake for Counter {
+		fn wake(self: Arc<Self>) { self.0.fetch_add(1, Ordering::SeqCst); }
+	}
+
+	#[test]
+	fn test_time_driver() {
+		let driver: RadixTimeDriver<FakeChannel, 4> = RadixTimeDriver::new(FakeChannel::default());
+		driver.channel().now.store(1000, Ordering::SeqCst);
+
+		let counters: Vec<Arc<Counter>> = (0..5).map(|_| Arc::new(Counter(AtomicUsize::new(0)))).collect();
+		let wakers: Vec<Waker> = counters.iter().map(|c| Waker::from(c.clone())).collect();
+		let woken = |i: usize| counters[i].0.load(Ordering::SeqCst);
+
+		driver.schedule_wake(1500, &wakers[0]);
+		driver.schedule_wake(1200, &wakers[1]);
+		driver.schedule_wake(1900, &wakers[1]);
+		driver.schedule_wake(1100, &wakers[0]);
+		driver.schedule_wake(u64::MAX, &wakers[2]);
+		assert_eq!(driver.pending(), 3);
+		assert_eq!(driver.channel().alarm.load(Ordering::SeqCst), 1100);
+
+		// a deadline in the past fires right away
+		driver.schedule_wake(900, &wakers[3]);
+		assert_eq!((woken(3), driver.pending()), (1, 3));
+
+		driver.channel().now.store(1300, Ordering::SeqCst);
+		driver.on_alarm();
+		assert_eq!((woken(0), woken(1), woken(2)), (1, 1, 0));
+		assert_eq!(driver.pending(), 1);
+
+		// a full queue wakes the timer at once instead
+		driver.schedule_wake(5000, &wakers[0]);
+		driver.schedule_wake(5000, &wakers[1]);
+		driver.schedule_wake(6000, &wakers[4]);
+		assert_eq!((woken(4), driver.pending()), (0, 4));
+		driver.schedule_wake(7000, &wakers[3]);
+		assert_eq!((woken(3), driver.pending()), (2, 4));
+	}
+
+	#[test]
+	fn test_stale_entries() {
+		let driver: RadixTimeDriver<FakeChannel, 4> = RadixTimeDriver::new(FakeChannel::default());
+		let counters: Vec<Arc<Counter>> = (0..2).map(|_| Arc::new(Counter(AtomicUsize::new(0)))).collect();
+		let wakers: Vec<Waker> = counters.iter().map(|c| Waker::from(c.clone())).collect();
+
+		// moving a timer forward must not use up the slots of other timers
+		for at in (0..8).map(|i| 2000 - i * 100) { driver.schedule_wake(at, &wakers[0]); }
+		driver.schedule_wake(3000, &wakers[1]);
+		assert_eq!((counters[1].0.load(Ordering::SeqCst), driver.pending()), (0, 2));
+
+		driver.channel().now.store(3000, Ordering::SeqCst);
+		driver.on_alarm();
+		assert_eq!(counters.iter().map(|c| c.0.load(Ordering::SeqCst)).collect::<Vec<_>>(), vec![1, 1]);
+	}
+}
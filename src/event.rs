@@ -0,0 +1,322 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: event.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashMap;
+use crate::clock::{Clock, ManualClock};
+use crate::timer::{Period, Repeat};
+use crate::RadixHeap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventToken(u64);
+
+// discrete event queue: time only moves on as events are handled, or up
+// to the time of its clock with "tick()"
+#[derive(Clone, Debug)]
+pub struct EventQueue<E, C: Clock = ManualClock> {
+	// every heap entry carries a ticket, entries whose ticket no longer
+	// matches the one of their event were cancelled or rescheduled
+	heap: RadixHeap<(EventToken, u64)>,
+	events: HashMap<EventToken, (u32, u64, E)>,
+	periods: HashMap<EventToken, Period<E>>,
+	next: u64,
+	now: u32,
+	clock: C
+}
+
+impl<E> EventQueue<E> {
+	pub fn new() -> EventQueue<E> { EventQueue::with_clock(ManualClock::new()) }
+}
+
+impl<E, C: Clock> EventQueue<E, C> {
+	// the queue starts at the current time of "clock"
+	pub fn with_clock(clock: C) -> EventQueue<E, C> {
+		let now = clock.now();
+		EventQueue { heap: RadixHeap::new(), events: HashMap::new(), periods: HashMap::new(), next: 0, now, clock }
+	}
+
+	pub fn clock(&self) -> &C { &self.clock }
+
+	// logical time of the event handled last
+	pub fn now(&self) -> u32 { self.now }
+
+	pub fn len(&self) -> usize { self.events.len() }
+
+	pub fn is_empty(&self) -> bool { self.events.is_empty() }
+
+	pub fn contains(&self, token: EventToken) -> bool { self.events.contains_key(&token) }
+
+	// time of the next event; this can be the time of an already cancelled
+	// event
+	pub fn next_time(&self) -> Option<u32> {
+		if self.is_empty() { None } else { self.heap.peek_key() }
+	}
+
+	// the returned token cancels or reschedules the event
+	pub fn schedule(&mut self, at: u32, event: E) -> Result<EventToken, &'static str> {
+		let token = EventToken(self.ticket());
+		let ticket = self.enqueue(at, token)?;
+
+		self.events.insert(token, (at, ticket, event));
+		Ok(token)
+	}
+
+	pub fn reschedule(&mut self, token: EventToken, at: u32) -> Result<(), &'static str> {
+		if !self.events.contains_key(&token) { return Err("unknown event"); }
+
+		let ticket = self.enqueue(at, token)?;
+		let event = self.events.get_mut(&token).unwrap();
+		event.0 = at;
+		event.1 = ticket;
+
+		self.compact();
+		Ok(())
+	}
+
+	pub fn cancel(&mut self, token: EventToken) -> Option<E> {
+		self.periods.remove(&token);
+		let event = self.events.remove(&token).map(|(_, _, e)| e);
+		if event.is_some() { self.compact(); }
+		event
+	}
+
+	// handles the next event; the handler may schedule further events
+	pub fn step<F: FnMut(&mut EventQueue<E, C>, u32, E)>(&mut self, mut handler: F) -> bool {
+		while let Some((at, entry)) = self.heap.pop() {
+			if let Some(event) = self.take(entry, at) {
+				self.now = at;
+				handler(self, at, event);
+				return true;
+			}
+		}
+
+		// only cancelled entries were left, which may have moved the bound
+		// of the heap past the current time
+		self.heap = RadixHeap::new();
+		false
+	}
+
+	// handles all events due at or before "until" (including those scheduled
+	// by handlers in the meantime), advances the clock to "until" and
+	// returns the number of events handled
+	pub fn run_until<F: FnMut(&mut EventQueue<E, C>, u32, E)>(&mut self, until: u32, mut handler: F)
+		-> usize {
+		let mut handled = 0;
+
+		while self.heap.peek_key().is_some_and(|at| at <= until) {
+			let (at, entry) = self.heap.pop().unwrap();
+
+			if let Some(event) = self.take(entry, until) {
+				self.now = at;
+				handler(self, at, event);
+				handled += 1;
+			}
+		}
+
+		if until > self.now { self.now = until; }
+		handled
+	}
+
+	// runs until the current time of the clock
+	pub fn tick<F: FnMut(&mut EventQueue<E, C>, u32, E)>(&mut self, handler: F) -> usize {
+		let now = self.clock.now();
+		self.run_until(now, handler)
+	}
+
+	fn ticket(&mut self) -> u64 {
+		self.next += 1;
+		self.next
+	}
+
+	fn enqueue(&mut self, at: u32, token: EventToken) -> Result<u64, &'static str> {
+		if at < self.now { return Err("event scheduled in the past"); }
+
+		let ticket = self.ticket();
+		self.heap.push(at, (token, ticket)).map_err(|_| "event scheduled in the past")?;
+		Ok(ticket)
+	}
+
+	// removes the event belonging to a heap entry unless the entry is stale;
+	// a repeating event fired at "now" is queued again and handed out as a
+	// copy instead
+	fn take(&mut self, (token, ticket): (EventToken, u64), now: u32) -> Option<E> {
+		let &(at, t, _) = self.events.get(&token)?;
+		if t != ticket { return None; }
+
+		if let Some(period) = self.periods.get(&token) {
+			let (next, copy) = (period.next(at, now), period.copy);
+
+			if let Some(next) = next {
+				let ticket = self.enqueue(next, token).unwrap();
+				let event = self.events.get_mut(&token).unwrap();
+				(event.0, event.1) = (next, ticket);
+				return Some(copy(&event.2));
+			}
+
+			self.periods.remove(&token);
+		}
+
+		self.events.remove(&token).map(|(_, _, e)| e)
+	}
+
+	// rebuilds the heap once stale entries outnumber the live ones
+	fn compact(&mut self) {
+		if self.heap.len() <= 2 * self.events.len() + 32 { return; }
+
+		self.heap = RadixHeap::new();
+
+		for (&token, &(at, ticket, _)) in &self.events {
+			self.heap.push(at, (token, ticket)).unwrap();
+		}
+	}
+}
+
+impl<E: Clone, C: Clock> EventQueue<E, C> {
+	// fires every "interval" ticks, first at "now() + interval", until the
+	// event is cancelled. "step()" fires it once per period, "run_until()"
+	// and "tick()" once per call like "TimerWheel::advance()", the next
+	// time depending on "repeat"
+	pub fn schedule_repeating(&mut self, interval: u32, repeat: Repeat, event: E) -> Result<EventToken, &'static str> {
+		let period = Period::new(interval, repeat);
+		let token = self.schedule(self.now.saturating_add(interval), event)?;
+		self.periods.insert(token, period);
+		Ok(token)
+	}
+}
+
+impl<E> Default for EventQueue<E> {
+	fn default() -> EventQueue<E> { EventQueue::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_event_queue() {
+		let mut queue = EventQueue::new();
+		let mut log: Vec<(u32, &str)> = Vec::new();
+
+		queue.schedule(10, "ping").unwrap();
+		queue.schedule(3, "start").unwrap();
+
+		let handled = queue.run_until(20, |q, at, event| {
+			log.push((at, event));
+			// reentrant scheduling from within the handler
+			if event == "ping" { q.schedule(at + 5, "pong").unwrap(); }
+			if event == "pong" { q.schedule(at + 5, "ping").unwrap(); }
+		});
+
+		assert_eq!(handled, 4);
+		assert_eq!(log, vec![(3, "start"), (10, "ping"), (15, "pong"), (20, "ping")]);
+		assert_eq!(queue.now(), 20);
+		assert_eq!(queue.next_time(), Some(25));
+		assert!(queue.schedule(19, "late").is_err());
+	}
+
+	#[test]
+	fn test_cancellation() {
+		let mut queue = EventQueue::new();
+		let timeout = queue.schedule(30, "timeout").unwrap();
+		let retry = queue.schedule(10, "retry").unwrap();
+		queue.schedule(20, "reply").unwrap();
+
+		assert_eq!(queue.cancel(timeout), Some("timeout"));
+		assert_eq!(queue.cancel(timeout), None);
+		assert_eq!(queue.reschedule(retry, 25), Ok(()));
+		assert_eq!(queue.reschedule(timeout, 40), Err("unknown event"));
+
+		let mut log = Vec::new();
+		assert_eq!(queue.run_until(100, |_, at, event| log.push((at, event))), 2);
+		assert_eq!(log, vec![(20, "reply"), (25, "retry")]);
+		assert!(queue.is_empty() && !queue.contains(retry));
+
+		// cancelled events left in the heap do not hold back the clock
+		let late = queue.schedule(500, "late").unwrap();
+		queue.cancel(late);
+		assert!(!queue.step(|_, _, _| {}));
+		assert!(queue.schedule(101, "soon").is_ok());
+	}
+	#[test]
+	fn test_repeating() {
+		let mut queue = EventQueue::new();
+		let rate = queue.schedule_repeating(10, Repeat::FixedRate, "rate").unwrap();
+		queue.schedule_repeating(10, Repeat::FixedDelay, "delay").unwrap();
+
+		let mut log = Vec::new();
+		for _ in 0..4 { queue.step(|_, at, event| log.push((at, event))); }
+		assert_eq!(log, vec![(10, "rate"), (10, "delay"), (20, "rate"), (20, "delay")]);
+
+		// handled at 45, the fixed rate event fires once for 30 and 40 and
+		// moves on to 50, the fixed delay one moves on to 55
+		log.clear();
+		assert_eq!(queue.run_until(45, |_, at, event| log.push((at, event))), 2);
+		assert_eq!(queue.run_until(50, |_, at, event| log.push((at, event))), 1);
+		assert_eq!(log, vec![(30, "rate"), (30, "delay"), (50, "rate")]);
+
+		assert_eq!(queue.cancel(rate), Some("rate"));
+		assert_eq!((queue.len(), queue.next_time()), (1, Some(55)));
+	}
+
+	#[test]
+	fn test_repeating_at_end_of_time() {
+		let mut queue = EventQueue::new();
+		queue.schedule(u32::MAX - 10, ()).unwrap();
+		queue.step(|_, _, _| {});
+		queue.schedule_repeating(7, Repeat::FixedRate, ()).unwrap();
+
+		// fires a last time, as no later time is left
+		assert_eq!(queue.run_until(u32::MAX, |_, _, _| {}), 1);
+		assert_eq!(queue.run_until(u32::MAX, |_, _, _| {}), 0);
+		assert!(queue.is_empty());
+	}
+
+	#[test]
+	fn test_clock() {
+		let clock = ManualClock::new();
+		clock.set(100);
+
+		let mut queue = EventQueue::with_clock(clock.clone());
+		assert!(queue.schedule(90, "past").is_err());
+		queue.schedule(120, "a").unwrap();
+		queue.schedule(140, "b").unwrap();
+
+		clock.advance(30);
+		let mut log = Vec::new();
+		assert_eq!(queue.tick(|_, at, event| log.push((at, event))), 1);
+		assert_eq!((log, queue.now()), (vec![(120, "a")], 130));
+	}
+
+	#[test]
+	fn test_closure_events() {
+		type Action = Box<dyn FnOnce(&mut Vec<u32>)>;
+
+		let mut queue: EventQueue<Action> = EventQueue::new();
+		queue.schedule(7, Box::new(|log| log.push(7))).unwrap();
+		queue.schedule(2, Box::new(|log| log.push(2))).unwrap();
+
+		let mut log = Vec::new();
+		queue.run_until(10, |_, _, event| event(&mut log));
+		assert_eq!(log, vec![2, 7]);
+	}
+}
@@ -0,0 +1,114 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: expiring.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::timer::{TimerId, TimerWheel};
+
+// map whose entries carry a deadline; expired entries stay accessible
+// until they are evicted by "purge_expired"
+#[derive(Clone, Debug)]
+pub struct ExpiringMap<K: Clone + Eq + Hash, V> {
+	entries: HashMap<K, (TimerId, V)>,
+	deadlines: TimerWheel<K>
+}
+
+impl<K: Clone + Eq + Hash, V> ExpiringMap<K, V> {
+	pub fn new() -> ExpiringMap<K, V> {
+		ExpiringMap { entries: HashMap::new(), deadlines: TimerWheel::new() }
+	}
+
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+	pub fn contains_key(&self, key: &K) -> bool { self.entries.contains_key(key) }
+
+	pub fn get(&self, key: &K) -> Option<&V> { self.entries.get(key).map(|(_, v)| v) }
+
+	pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+		self.entries.get_mut(key).map(|(_, v)| v)
+	}
+
+	pub fn deadline(&self, key: &K) -> Option<u32> {
+		self.entries.get(key).and_then(|&(id, _)| self.deadlines.deadline(id))
+	}
+
+	// inserts or replaces an entry, replacing its deadline as well
+	pub fn insert(&mut self, key: K, value: V, deadline: u32) -> Option<V> {
+		let id = self.deadlines.insert(deadline, key.clone());
+
+		self.entries.insert(key, (id, value)).map(|(old, v)| {
+			self.deadlines.cancel(old);
+			v
+		})
+	}
+
+	pub fn remove(&mut self, key: &K) -> Option<V> {
+		self.entries.remove(key).map(|(id, v)| {
+			self.deadlines.cancel(id);
+			v
+		})
+	}
+
+	// evicts all entries whose deadline is at or before "now"
+	pub fn purge_expired(&mut self, now: u32) -> Vec<(K, V)> {
+		self.deadlines.advance(now).into_iter()
+			.filter_map(|(_, key)| self.entries.remove(&key).map(|(_, v)| (key, v)))
+			.collect()
+	}
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.deadlines.clear();
+	}
+}
+
+impl<K: Clone + Eq + Hash, V> Default for ExpiringMap<K, V> {
+	fn default() -> ExpiringMap<K, V> { ExpiringMap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_expiring_map() {
+		let mut map = ExpiringMap::new();
+		map.insert("session-a", 1, 100);
+		map.insert("session-b", 2, 50);
+		map.insert("session-c", 3, 70);
+
+		// refreshing an entry moves its deadline
+		assert_eq!(map.insert("session-b", 20, 120), Some(2));
+		assert_eq!(map.deadline(&"session-b"), Some(120));
+		assert_eq!(map.remove(&"session-c"), Some(3));
+
+		assert!(map.purge_expired(90).is_empty());
+		assert_eq!(map.purge_expired(110), vec![("session-a", 1)]);
+		assert_eq!(map.get(&"session-b"), Some(&20));
+		assert_eq!(map.purge_expired(120), vec![("session-b", 20)]);
+		assert!(map.is_empty());
+	}
+}
@@ -0,0 +1,450 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: external.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use crate::RadixHeap;
+
+// binary encoding of values written to spill files
+pub trait Spill: Sized {
+	fn spill<W: Write>(&self, out: &mut W) -> io::Result<()>;
+	fn restore<R: Read>(input: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! spill_integer {
+	($($t:ty),*) => {$(
+		impl Spill for $t {
+			fn spill<W: Write>(&self, out: &mut W) -> io::Result<()> { out.write_all(&self.to_le_bytes()) }
+
+			fn restore<R: Read>(input: &mut R) -> io::Result<$t> {
+				let mut bytes = [0u8; std::mem::size_of::<$t>()];
+				input.read_exact(&mut bytes)?;
+				Ok(<$t>::from_le_bytes(bytes))
+			}
+		}
+	)*}
+}
+
+spill_integer!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl Spill for usize {
+	fn spill<W: Write>(&self, out: &mut W) -> io::Result<()> { (*self as u64).spill(out) }
+	fn restore<R: Read>(input: &mut R) -> io::Result<usize> { Ok(u64::restore(input)? as usize) }
+}
+
+impl Spill for () {
+	fn spill<W: Write>(&self, _: &mut W) -> io::Result<()> { Ok(()) }
+	fn restore<R: Read>(_: &mut R) -> io::Result<()> { Ok(()) }
+}
+
+impl Spill for String {
+	fn spill<W: Write>(&self, out: &mut W) -> io::Result<()> {
+		self.len().spill(out)?;
+		out.write_all(self.as_bytes())
+	}
+
+	fn restore<R: Read>(input: &mut R) -> io::Result<String> {
+		let mut bytes = vec![0u8; usize::restore(input)?];
+		input.read_exact(&mut bytes)?;
+		String::from_utf8(bytes).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+	}
+}
+
+impl<A: Spill, B: Spill> Spill for (A, B) {
+	fn spill<W: Write>(&self, out: &mut W) -> io::Result<()> {
+		self.0.spill(out)?;
+		self.1.spill(out)
+	}
+
+	fn restore<R: Read>(input: &mut R) -> io::Result<(A, B)> { Ok((A::restore(input)?, B::restore(input)?)) }
+}
+
+// cold bucket living in an append-only file; a file is truncated when it
+// is opened while empty, so files left behind in the directory are ignored
+struct SpillFile {
+	path: PathBuf,
+	writer: Option<BufWriter<File>>,
+	count: usize
+}
+
+// radix heap keeping only its low ("hot") buckets in memory; the items of
+// higher buckets are appended to one spill file per bucket and read back
+// sequentially once their bucket becomes the lowest non-empty one, so
+// at no point does a cold bucket have to fit into memory
+pub struct ExternalRadixHeap<V: Spill> {
+	hot: Vec<Vec<(u32, V)>>,
+	cold: Vec<SpillFile>,
+	toplast: u32,
+	length: usize
+}
+
+impl<V: Spill> ExternalRadixHeap<V> {
+	// buckets from index "hot" upwards spill into files inside "dir"
+	pub fn new<P: AsRef<Path>>(dir: P, hot: usize) -> io::Result<ExternalRadixHeap<V>> {
+		let hot = hot.clamp(1, 33);
+		fs::create_dir_all(dir.as_ref())?;
+
+		Ok(ExternalRadixHeap {
+			hot: (0..hot).map(|_| Vec::new()).collect(),
+			cold: (hot..33).map(|i| SpillFile {
+				path: dir.as_ref().join(format!("bucket-{}.spill", i)),
+				writer: None,
+				count: 0
+			}).collect(),
+			toplast: 0,
+			length: 0
+		})
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	// number of items currently kept on disk
+	pub fn spilled(&self) -> usize { self.cold.iter().map(|f| f.count).sum() }
+
+	pub fn push(&mut self, key: u32, val: V) -> io::Result<()> {
+		if key < self.toplast { return Err(io::Error::new(ErrorKind::InvalidInput, "key too small")); }
+
+		self.place(key, val)?;
+		self.length += 1;
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> io::Result<Option<(u32, V)>> {
+		if let Some(index) = self.hot.iter().position(|b| !b.is_empty()) {
+			if index == 0 {
+				self.length -= 1;
+				return Ok(self.hot[0].pop());
+			}
+
+			// the items of a hot bucket only move to lower, hence hot,
+			// buckets, which cannot fail
+			let bucket = std::mem::take(&mut self.hot[index]);
+			let min = (0..bucket.len()).min_by_key(|&i| bucket[i].0).unwrap();
+			self.toplast = bucket[min].0;
+
+			let mut top = None;
+			for (i, (key, val)) in bucket.into_iter().enumerate() {
+				if i == min { top = Some((key, val)); } else { self.place(key, val)?; }
+			}
+
+			self.length -= 1;
+			return Ok(top);
+		}
+
+		match self.cold.iter().position(|f| f.count > 0) {
+			Some(index) => {
+				let top = self.pop_cold(index)?;
+				self.length -= 1;
+				Ok(Some(top))
+			},
+			None => Ok(None)
+		}
+	}
+
+	// redistributes a spilled bucket with two sequential passes over its
+	// file: the first finds the minimum, the second moves all other items.
+	// All lower buckets are empty beforehand, so a failing second pass is
+	// undone by emptying them again, leaving the heap as it was.
+	fn pop_cold(&mut self, index: usize) -> io::Result<(u32, V)> {
+		let file = &mut self.cold[index];
+		if let Some(writer) = file.writer.as_mut() { writer.flush()?; }
+		file.writer = None;
+
+		let (path, count) = (file.path.clone(), file.count);
+		let mut reader = BufReader::new(File::open(&path)?);
+		let mut min = (u32::MAX, 0);
+		for i in 0..count {
+			let key = u32::restore(&mut reader)?;
+			V::restore(&mut reader)?;
+			if key < min.0 { min = (key, i); }
+		}
+
+		let toplast = self.toplast;
+		self.toplast = min.0;
+
+		match self.redistribute(&path, count, min.1) {
+			Ok(top) => {
+				self.cold[index].count = 0;
+				// an empty file left behind is truncated once reopened
+				let _ = fs::remove_file(&path);
+				Ok(top)
+			},
+			Err(e) => {
+				self.toplast = toplast;
+				for bucket in &mut self.hot { bucket.clear(); }
+
+				for file in &mut self.cold[..index] {
+					file.writer = None;
+					if file.count > 0 { let _ = fs::remove_file(&file.path); }
+					file.count = 0;
+				}
+
+				Err(e)
+			}
+		}
+	}
+
+	// places all items of a spill file but the one at "min", which is
+	// returned
+	fn redistribute(&mut self, path: &Path, count: usize, min: usize) -> io::Result<(u32, V)> {
+		let mut reader = BufReader::new(File::open(path)?);
+		let mut top = None;
+
+		for i in 0..count {
+			let (key, val) = (u32::restore(&mut reader)?, V::restore(&mut reader)?);
+			if i == min { top = Some((key, val)); } else { self.place(key, val)?; }
+		}
+
+		Ok(top.unwrap())
+	}
+
+	fn place(&mut self, key: u32, val: V) -> io::Result<()> {
+		let index = if key == self.toplast { 0 } else { 32 - (key ^ self.toplast).leading_zeros() as usize };
+		if index < self.hot.len() {
+			self.hot[index].push((key, val));
+			return Ok(());
+		}
+
+		let file = &mut self.cold[index - self.hot.len()];
+		if file.writer.is_none() {
+			let (append, truncate) = (file.count > 0, file.count == 0);
+			let out = OpenOptions::new().write(true).create(true).append(append).truncate(truncate).open(&file.path)?;
+			file.writer = Some(BufWriter::new(out));
+		}
+
+		let writer = file.writer.as_mut().unwrap();
+		key.spill(writer)?;
+		val.spill(writer)?;
+		file.count += 1;
+		Ok(())
+	}
+}
+
+impl<V: Spill> Drop for ExternalRadixHeap<V> {
+	fn drop(&mut self) {
+		for file in &mut self.cold {
+			file.writer = None;
+			if file.count > 0 { let _ = fs::remove_file(&file.path); }
+		}
+	}
+}
+
+// writes ascending records into a run file readable by "SortedRun" and
+// returns the number of records written
+pub fn write_run<P: AsRef<Path>, V: Spill, I: IntoIterator<Item = (u32, V)>>(path: P, records: I) -> io::Result<usize> {
+	let mut writer = BufWriter::new(File::create(path)?);
+	let (mut count, mut last) = (0, 0);
+
+	for (key, val) in records {
+		if key < last { return Err(io::Error::new(ErrorKind::InvalidInput, "run is not sorted")); }
+		key.spill(&mut writer)?;
+		val.spill(&mut writer)?;
+		(count, last) = (count + 1, key);
+	}
+
+	writer.flush()?;
+	Ok(count)
+}
+
+// sequential reader of a run file of ascending "(key, value)" records
+pub struct SortedRun<V: Spill> {
+	reader: BufReader<File>,
+	last: u32,
+	values: std::marker::PhantomData<V>
+}
+
+impl<V: Spill> SortedRun<V> {
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<SortedRun<V>> {
+		Ok(SortedRun { reader: BufReader::new(File::open(path)?), last: 0, values: std::marker::PhantomData })
+	}
+
+	fn read(&mut self) -> io::Result<Option<(u32, V)>> {
+		// a clean end of file may only occur between two records
+		if self.reader.fill_buf()?.is_empty() { return Ok(None); }
+
+		let (key, val) = (u32::restore(&mut self.reader)?, V::restore(&mut self.reader)?);
+		if key < self.last { return Err(io::Error::new(ErrorKind::InvalidData, "run is not sorted")); }
+		self.last = key;
+		Ok(Some((key, val)))
+	}
+}
+
+impl<V: Spill> Iterator for SortedRun<V> {
+	type Item = io::Result<(u32, V)>;
+
+	fn next(&mut self) -> Option<Self::Item> { self.read().transpose() }
+}
+
+// ascending stream of the records of several runs and an in-memory heap;
+// the heap is drained as the merge proceeds
+pub struct MergeRuns<V: Clone + Debug + Ord + Spill> {
+	runs: Vec<SortedRun<V>>,
+	memory: RadixHeap<V>,
+	heads: Vec<Option<V>>,
+	// holds the key of the current head of every non-exhausted run, with
+	// index "runs.len()" standing for the in-memory heap
+	heap: RadixHeap<usize>,
+	error: Option<io::Error>,
+	failed: bool
+}
+
+pub fn merge_runs<V: Clone + Debug + Ord + Spill>(runs: Vec<SortedRun<V>>, memory: RadixHeap<V>) -> io::Result<MergeRuns<V>> {
+	let heads = (0..=runs.len()).map(|_| None).collect();
+	let mut merge = MergeRuns { runs, memory, heads, heap: RadixHeap::new(), error: None, failed: false };
+
+	for index in 0..=merge.runs.len() { merge.refill(index)?; }
+	Ok(merge)
+}
+
+impl<V: Clone + Debug + Ord + Spill> MergeRuns<V> {
+	fn refill(&mut self, index: usize) -> io::Result<()> {
+		let next = if index == self.runs.len() { self.memory.pop() } else { self.runs[index].read()? };
+
+		if let Some((key, val)) = next {
+			// runs check their own order, so this only fails on a broken run
+			self.heap.push(key, index).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+			self.heads[index] = Some(val);
+		}
+
+		Ok(())
+	}
+}
+
+impl<V: Clone + Debug + Ord + Spill> Iterator for MergeRuns<V> {
+	type Item = io::Result<(u32, V)>;
+
+	// stops after the first error, which is reported once the record
+	// preceding it has been handed out
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.failed { return None; }
+		if let Some(e) = self.error.take() {
+			self.failed = true;
+			return Some(Err(e));
+		}
+
+		let (key, index) = self.heap.pop()?;
+		let val = self.heads[index].take().unwrap();
+		self.error = self.refill(index).err();
+
+		Some(Ok((key, val)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_external() {
+		let dir = std::env::temp_dir().join(format!("radixheap-external-{}", std::process::id()));
+		let mut heap = ExternalRadixHeap::new(&dir, 8).unwrap();
+		let mut rng = rand::thread_rng();
+		let mut keys: Vec<u32> = (0..5000).map(|_| rng.gen()).collect();
+
+		for &key in &keys { heap.push(key, (key, format!("{}", key))).unwrap(); }
+		assert!(heap.spilled() > 4900);
+		heap.push(5, (5, String::from("5"))).unwrap();
+		keys.push(5);
+		keys.sort_unstable();
+
+		for &key in &keys {
+			let (k, (v, s)) = heap.pop().unwrap().unwrap();
+			assert_eq!((k, v), (key, key));
+			assert_eq!(s, key.to_string());
+		}
+
+		assert_eq!(heap.pop().unwrap(), None);
+		assert!(heap.push(keys[0], (0, String::new())).is_err());
+
+		drop(heap);
+		assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+		fs::remove_dir(&dir).unwrap();
+	}
+
+	#[test]
+	fn test_spill_errors() {
+		let dir = std::env::temp_dir().join(format!("radixheap-stale-{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		for i in 1..33 { fs::write(dir.join(format!("bucket-{}.spill", i)), b"left behind by a crash").unwrap(); }
+
+		let mut heap = ExternalRadixHeap::new(&dir, 1).unwrap();
+		for key in [40u32, 7, 1 << 20, 9] { heap.push(key, u64::from(key)).unwrap(); }
+		assert_eq!(heap.pop().unwrap(), Some((7, 7)));
+
+		// a failing pop leaves the heap untouched
+		let spilled = heap.spilled();
+		fs::rename(dir.join("bucket-4.spill"), dir.join("moved")).unwrap();
+		assert!(heap.pop().is_err());
+		assert_eq!((heap.len(), heap.spilled()), (3, spilled));
+
+		fs::rename(dir.join("moved"), dir.join("bucket-4.spill")).unwrap();
+		let keys: Vec<u32> = std::iter::from_fn(|| heap.pop().unwrap()).map(|(k, _)| k).collect();
+		assert_eq!(keys, vec![9, 40, 1 << 20]);
+		drop(heap);
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn test_merge_runs() {
+		let dir = std::env::temp_dir().join(format!("radixheap-runs-{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		let mut rng = rand::thread_rng();
+		let mut model: Vec<u32> = Vec::new();
+
+		for i in 0..3 {
+			let mut keys: Vec<u32> = (0..1000).map(|_| rng.gen_range(0, 1 << 20)).collect();
+			keys.sort_unstable();
+			model.extend(&keys);
+			assert_eq!(write_run(dir.join(format!("run-{}", i)), keys.iter().map(|&k| (k, k as u64))).unwrap(), 1000);
+		}
+
+		let mut memory = RadixHeap::new();
+		for _ in 0..500 {
+			let key = rng.gen_range(0, 1 << 20);
+			memory.push(key, key as u64).unwrap();
+			model.push(key);
+		}
+		model.sort_unstable();
+
+		let runs = (0..3).map(|i| SortedRun::open(dir.join(format!("run-{}", i))).unwrap()).collect();
+		let merged: Vec<u32> = merge_runs(runs, memory).unwrap().map(|r| {
+			let (key, val) = r.unwrap();
+			assert_eq!(key as u64, val);
+			key
+		}).collect();
+		assert_eq!(merged, model);
+
+		assert!(write_run(dir.join("bad"), vec![(3, ()), (1, ())]).is_err());
+		let mut bad = File::create(dir.join("bad")).unwrap();
+		for key in [3u32, 1] { (key, ()).spill(&mut bad).unwrap(); }
+		let errors: Vec<io::Result<(u32, ())>> = merge_runs(vec![SortedRun::open(dir.join("bad")).unwrap()], RadixHeap::new()).unwrap().collect();
+		assert!(errors.len() == 2 && errors[1].is_err());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,125 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: frozen.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+use crate::RadixHeap;
+
+// immutable form of a heap for read-heavy phases: all items are sorted by
+// key in one contiguous slice, so peeks are constant, lookups and range
+// queries are binary searches, and clones only bump a reference count
+#[derive(Clone, Debug)]
+pub struct FrozenRadixHeap<V> {
+	items: Arc<[(u32, V)]>,
+	toplast: u32
+}
+
+impl<V> FrozenRadixHeap<V> {
+	// "items" must be sorted by key and not below "toplast"
+	pub(crate) fn from_sorted(items: Vec<(u32, V)>, toplast: u32) -> FrozenRadixHeap<V> {
+		FrozenRadixHeap { items: items.into(), toplast }
+	}
+
+	pub fn len(&self) -> usize { self.items.len() }
+
+	pub fn is_empty(&self) -> bool { self.items.is_empty() }
+
+	pub fn bound(&self) -> u32 { self.toplast }
+
+	pub fn peek(&self) -> Option<(u32, &V)> { self.items.first().map(|(k, v)| (*k, v)) }
+
+	pub fn contains_key(&self, key: u32) -> bool {
+		self.items.binary_search_by_key(&key, |&(k, _)| k).is_ok()
+	}
+
+	// all items with keys in "range", sorted by key
+	pub fn range<R: RangeBounds<u32>>(&self, range: R) -> &[(u32, V)] {
+		let start = match range.start_bound() {
+			Bound::Included(&low) => self.items.partition_point(|&(k, _)| k < low),
+			Bound::Excluded(&low) => self.items.partition_point(|&(k, _)| k <= low),
+			Bound::Unbounded => 0
+		};
+		let end = match range.end_bound() {
+			Bound::Included(&high) => self.items.partition_point(|&(k, _)| k <= high),
+			Bound::Excluded(&high) => self.items.partition_point(|&(k, _)| k < high),
+			Bound::Unbounded => self.items.len()
+		};
+
+		&self.items[start..end.max(start)]
+	}
+
+	pub fn as_slice(&self) -> &[(u32, V)] { &self.items }
+
+	pub fn iter(&self) -> std::slice::Iter<'_, (u32, V)> { self.items.iter() }
+}
+
+impl<V: Clone + Debug + Ord> FrozenRadixHeap<V> {
+	// mutable heap with the same items and bound
+	pub fn thaw(&self) -> RadixHeap<V> {
+		let mut buckets: Vec<Vec<(u32, V)>> = (0..33).map(|_| Vec::new()).collect();
+		for (key, val) in self.items.iter() {
+			let index = if *key == self.toplast { 0 } else { 32 - (key ^ self.toplast).leading_zeros() as usize };
+			buckets[index].push((*key, val.clone()));
+		}
+
+		// bucket 0 is popped from its end
+		buckets[0].reverse();
+		RadixHeap::from_raw_buckets(self.toplast, buckets).unwrap()
+	}
+}
+
+impl<'a, V> IntoIterator for &'a FrozenRadixHeap<V> {
+	type Item = &'a (u32, V);
+	type IntoIter = std::slice::Iter<'a, (u32, V)>;
+
+	fn into_iter(self) -> Self::IntoIter { self.items.iter() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_freeze_thaw() {
+		let mut heap = RadixHeap::new();
+		for key in [30u32, 7, 900, 7, 64, 12, 5] { heap.push(key, key as u64).unwrap(); }
+		heap.pop();
+
+		let frozen = heap.freeze();
+		let copy = frozen.clone();
+		assert_eq!((frozen.len(), frozen.bound()), (6, 5));
+		assert_eq!(frozen.peek(), Some((7, &7)));
+		assert!(frozen.contains_key(64) && !frozen.contains_key(5));
+		assert_eq!(frozen.range(8..=64).iter().map(|&(k, _)| k).collect::<Vec<u32>>(), vec![12, 30, 64]);
+		assert_eq!(frozen.range(901..).len(), 0);
+		assert_eq!(frozen.range(31..=63).len(), 0);
+
+		let mut thawed = copy.thaw();
+		assert_eq!(thawed.push(4, 4), Err("key too small"));
+		thawed.push(5, 5).unwrap();
+		let keys: Vec<u32> = std::iter::from_fn(|| thawed.pop().map(|(k, _)| k)).collect();
+		assert_eq!(keys, vec![5, 7, 7, 12, 30, 64, 900]);
+	}
+}
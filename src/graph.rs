@@ -0,0 +1,103 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: graph.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+// weighted directed graph as consumed by the shortest path and spanning
+// tree routines; nodes are numbered from zero
+pub trait Graph {
+	fn node_count(&self) -> usize;
+
+	// "(target, weight)" of every edge leaving "node"
+	fn edges(&self, node: usize) -> impl Iterator<Item = (usize, u32)> + '_;
+}
+
+// adjacency lists: "graph[u]" holds all "(v, weight)" pairs of the edges
+// leaving node "u"
+impl Graph for [Vec<(usize, u32)>] {
+	fn node_count(&self) -> usize { self.len() }
+	fn edges(&self, node: usize) -> impl Iterator<Item = (usize, u32)> + '_ { self[node].iter().copied() }
+}
+
+impl Graph for Vec<Vec<(usize, u32)>> {
+	fn node_count(&self) -> usize { self.len() }
+	fn edges(&self, node: usize) -> impl Iterator<Item = (usize, u32)> + '_ { self[node].iter().copied() }
+}
+
+impl<const N: usize> Graph for [Vec<(usize, u32)>; N] {
+	fn node_count(&self) -> usize { N }
+	fn edges(&self, node: usize) -> impl Iterator<Item = (usize, u32)> + '_ { self[node].iter().copied() }
+}
+
+// compressed sparse rows: the edges leaving node "u" are
+// "targets[offsets[u]..offsets[u + 1]]" with the weights at the same
+// positions of "weights"
+#[derive(Clone, Copy, Debug)]
+pub struct Csr<'a> {
+	offsets: &'a [usize],
+	targets: &'a [u32],
+	weights: &'a [u32]
+}
+
+impl<'a> Csr<'a> {
+	pub fn new(offsets: &'a [usize], targets: &'a [u32], weights: &'a [u32]) -> Result<Csr<'a>, &'static str> {
+		if offsets.first() != Some(&0) || offsets.last() != Some(&targets.len()) { return Err("invalid offsets"); }
+		if offsets.windows(2).any(|w| w[0] > w[1]) { return Err("invalid offsets"); }
+		if weights.len() != targets.len() { return Err("weights do not match targets"); }
+
+		let nodes = offsets.len() - 1;
+		if targets.iter().any(|&t| t as usize >= nodes) { return Err("edge target out of range"); }
+
+		Ok(Csr { offsets, targets, weights })
+	}
+}
+
+impl<'a> Graph for Csr<'a> {
+	fn node_count(&self) -> usize { self.offsets.len() - 1 }
+
+	fn edges(&self, node: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+		let range = self.offsets[node]..self.offsets[node + 1];
+		self.targets[range.clone()].iter().zip(&self.weights[range]).map(|(&t, &w)| (t as usize, w))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::dijkstra::dijkstra;
+	use crate::mst::minimum_spanning_tree;
+
+	#[test]
+	fn test_csr() {
+		let lists: Vec<Vec<(usize, u32)>> = vec![vec![(1, 4), (2, 1)], vec![(2, 1), (0, 4)], vec![(1, 1), (0, 1)], vec![]];
+		let (offsets, targets, weights) = ([0, 2, 4, 6, 6], [1, 2, 2, 0, 1, 0], [4, 1, 1, 4, 1, 1]);
+		let csr = Csr::new(&offsets, &targets, &weights).unwrap();
+
+		assert_eq!(csr.node_count(), 4);
+		assert_eq!(csr.edges(1).collect::<Vec<_>>(), lists[1]);
+		assert_eq!(dijkstra(&csr, 0), dijkstra(&lists, 0));
+		assert_eq!(minimum_spanning_tree(&csr).1, 2);
+
+		assert_eq!(Csr::new(&[0, 2], &targets, &weights).unwrap_err(), "invalid offsets");
+		assert_eq!(Csr::new(&[0, 1], &[5], &[1]).unwrap_err(), "edge target out of range");
+	}
+}
@@ -0,0 +1,2093 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: heap.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use std::time::{Duration, Instant};
+#[cfg(feature = "rayon")]
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator,
+                  ParallelExtend, ParallelIterator, IndexedParallelIterator};
+#[cfg(feature = "rayon")]
+use rayon::slice::ParallelSliceMut;
+use crate::bucket::{Bucket, BucketView};
+use crate::frozen::FrozenRadixHeap;
+use crate::iter::{Cursor, IntoRadixBucketIter, PopWhile, RadixBucketIter};
+
+#[derive(Debug)]
+pub struct RadixHeap<V: Debug + Ord> {
+	pub(crate) buckets: Vec<Bucket<V>>,
+	toplast: u32,
+	length: usize,
+	// only collected after "enable_stats()"
+	stats: Option<Box<Stats>>,
+	observer: ObserverSlot<V>,
+	// bucket 0 is kept in reverse insertion order if set, so equal keys
+	// still pop from its end, but first in first out
+	fifo_ties: bool,
+	// changes whenever items are moved or removed, see "Cursor"
+	pub(crate) generation: u64,
+	// buckets up to this index are popped from without restructuring
+	soft_bits: usize
+}
+
+// configures a heap before it is built
+#[derive(Clone, Debug)]
+pub struct RadixHeapBuilder<V> {
+	capacity: usize,
+	fifo_ties: bool,
+	stats: bool,
+	soft_bits: usize,
+	profile: Option<TuningProfile>,
+	values: PhantomData<fn() -> V>
+}
+
+// notified about every item entering, leaving or moving inside a heap;
+// all methods do nothing by default
+pub trait Observer<V> {
+	fn inserted(&mut self, _key: u32, _val: &V, _bucket: usize) {}
+	fn extracted(&mut self, _key: u32, _val: &V) {}
+	fn relocated(&mut self, _key: u32, _val: &V, _from: usize, _to: usize) {}
+	fn cleared(&mut self) {}
+}
+
+// observers are neither printed nor cloned along with their heap
+struct ObserverSlot<V>(Option<Box<dyn Observer<V> + Send + Sync>>);
+
+impl<V> Debug for ObserverSlot<V> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(if self.0.is_some() { "Some(Observer)" } else { "None" })
+	}
+}
+
+// counters collected since statistics were enabled or last reset
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+	pub pushes: u64,
+	pub pops: u64,
+	// pushes failing because of a key smaller than the last popped one
+	pub rejected: u64,
+	// pops redistributing a bucket and the items moved by them
+	pub restructures: u64,
+	pub moved: u64,
+	// highest number of items ever held by each bucket
+	pub high_water: Vec<usize>
+}
+
+// bucket capacities learned from the high-water marks of a heap, used to
+// pre-size the buckets of later heaps running the same workload
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TuningProfile {
+	capacities: Vec<usize>
+}
+
+impl TuningProfile {
+	pub fn capacities(&self) -> &[usize] { &self.capacities }
+
+	// keeps the larger capacity of every bucket, e.g. over several runs
+	pub fn merge(&mut self, other: &TuningProfile) {
+		if self.capacities.len() < other.capacities.len() { self.capacities.resize(other.capacities.len(), 0); }
+		for (mine, theirs) in self.capacities.iter_mut().zip(&other.capacities) { *mine = (*mine).max(*theirs); }
+	}
+}
+
+// how "meld()" deals with heaps of different bounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MeldPolicy {
+	// the higher bound applies, items below it are handed back
+	Park,
+	// the higher bound applies, the meld fails if any item is below it
+	Reject,
+	// the lower bound applies, which requires the heap with the higher
+	// bound to be empty
+	Rebase
+}
+
+// a heap can be sent to another thread whenever its values can, and be
+// shared between threads whenever its values can
+#[allow(dead_code)]
+const _: () = {
+	fn send<T: Send>() {}
+	fn sync<T: Sync>() {}
+
+	fn heap_is_send<V: Clone + Debug + Ord + Send>() {
+		send::<RadixHeap<V>>();
+		send::<Bucket<V>>();
+		send::<IntoRadixBucketIter<V>>();
+	}
+
+	fn heap_is_sync<V: Clone + Debug + Ord + Sync>() {
+		sync::<RadixHeap<V>>();
+		sync::<Bucket<V>>();
+	}
+};
+
+impl<V: Clone + Debug + Ord> RadixHeap<V> {
+	pub fn new() -> RadixHeap<V> { RadixHeap::allocate(0) }
+
+	// reserves room for "capacity" items in total, spread evenly over all
+	// buckets, as it is unknown which buckets the keys will end up in
+	pub fn with_capacity(capacity: usize) -> RadixHeap<V> { RadixHeap::allocate(capacity.div_ceil(33)) }
+
+	#[deprecated(note = "use \"new()\" or \"with_capacity()\", which takes the total number of items")]
+	pub fn with_bucket_capacity(capacity: Option<usize>) -> RadixHeap<V> {
+		RadixHeap::allocate(capacity.unwrap_or(0))
+	}
+
+	fn allocate(per_bucket: usize) -> RadixHeap<V> {
+		RadixHeap {
+			buckets: (0..33).map(|i: usize| Bucket {
+				index: i,
+				top: None,
+				items: Vec::with_capacity(per_bucket)
+			}).collect(),
+			toplast: u32::MIN,
+			length: 0,
+			stats: None,
+			observer: ObserverSlot(None),
+			fifo_ties: false,
+			generation: 0,
+			soft_bits: 0
+		}
+	}
+
+	pub fn builder() -> RadixHeapBuilder<V> {
+		RadixHeapBuilder { capacity: 0, fifo_ties: false, stats: false, soft_bits: 0, profile: None, values: PhantomData }
+	}
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		// key smaller than key of last extracted element
+		if key < self.toplast {
+			#[cfg(feature = "tracing")]
+			tracing::debug!(key, bound = self.toplast, "rejected push");
+
+			if let Some(stats) = &mut self.stats { stats.rejected += 1; }
+			Err("key too small")
+		} else {
+			#[cfg(feature = "tracing")]
+			tracing::trace!(key, bucket = self.bucket_index(key), "push");
+
+			if let Some(stats) = &mut self.stats { stats.pushes += 1; }
+			#[cfg(feature = "metrics")]
+			metrics::gauge!("radixheap_length").increment(1.0);
+
+			let bucket = self.bucket_index(key);
+			if let Some(observer) = &mut self.observer.0 { observer.inserted(key, &val, bucket); }
+			self.place(key, val, self.fifo_ties)
+		}
+	}
+
+	// pushes "delta" keys past the bound, e.g. "fire in 5 ticks"; returns
+	// the absolute key used
+	pub fn push_after(&mut self, delta: u32, val: V) -> Result<u32, &'static str> {
+		let key = self.toplast.checked_add(delta).ok_or("key overflow")?;
+		self.push(key, val)?;
+		Ok(key)
+	}
+
+	// stores "val" under "key", replacing an equal value stored under a
+	// larger key; does nothing if an equal value is stored under a key
+	// not larger than "key" already. Returns whether the heap changed,
+	// which makes it the "relax" step of Dijkstra's algorithm.
+	pub fn push_or_replace(&mut self, key: u32, val: V) -> Result<bool, &'static str> {
+		// rejected by "push", so the rejection is counted as usual
+		if key < self.toplast { return Err(self.push(key, val).unwrap_err()); }
+
+		let found = self.buckets.iter().enumerate().find_map(|(index, bucket)| {
+			bucket.items.iter().position(|(_, v)| *v == val).map(|position| (index, position))
+		});
+
+		if let Some((bucket, position)) = found {
+			if self.buckets[bucket].items[position].0 <= key { return Ok(false); }
+			self.remove_at(bucket, position);
+		}
+
+		self.push(key, val).map(|_| true)
+	}
+
+	// moves all items of "other" into this heap, leaving it empty; returns
+	// the items parked by "MeldPolicy::Park". Neither heap is changed if
+	// the meld fails.
+	pub fn meld(&mut self, other: &mut RadixHeap<V>, policy: MeldPolicy) -> Result<Vec<(u32, V)>, &'static str> {
+		let high = self.toplast.max(other.toplast);
+		let low = self.toplast.min(other.toplast);
+		let below = |heap: &RadixHeap<V>| heap.peek_key().is_some_and(|k| k < high);
+
+		let bound = match policy {
+			MeldPolicy::Park => high,
+			MeldPolicy::Reject if below(self) || below(other) => return Err("items below the higher bound"),
+			MeldPolicy::Reject => high,
+			MeldPolicy::Rebase => {
+				let upper = if self.toplast > other.toplast { &*self } else { &*other };
+				if low != high && !upper.is_empty() { return Err("heap with the higher bound is not empty"); }
+				low
+			}
+		};
+
+		let mut parked = self.rebase(bound);
+		let mut items: Vec<(u32, V)> = Vec::with_capacity(other.length);
+		for bucket in &mut other.buckets { items.append(&mut bucket.items); }
+		other.clear();
+
+		for (key, val) in items {
+			if key < bound { parked.push((key, val)); } else { self.push(key, val).unwrap(); }
+		}
+
+		parked.sort_by_key(|&(k, _)| k);
+		Ok(parked)
+	}
+
+	// adds "delta" to every key and to the bound. Buckets keep their layout
+	// if "delta" is a multiple of the key range of the highest non-empty
+	// bucket, so only the keys are updated; all items get redistributed
+	// otherwise.
+	pub fn shift_keys(&mut self, delta: i64) -> Result<(), &'static str> {
+		let shift = |key: u32| u32::try_from(i64::from(key) + delta).map_err(|_| "key overflow");
+		let bound = shift(self.toplast)?;
+		if let Some((max, _)) = self.peek_max() { shift(max)?; }
+
+		let highest = self.buckets.iter().rposition(|b| !b.empty()).unwrap_or(0);
+		self.toplast = bound;
+		self.generation += 1;
+
+		if delta.rem_euclid(1i64 << highest) == 0 {
+			for bucket in &mut self.buckets {
+				for item in bucket.items.iter_mut().chain(bucket.top.as_mut()) { item.0 = shift(item.0).unwrap(); }
+			}
+
+			return Ok(());
+		}
+
+		let mut items: Vec<(usize, (u32, V))> = Vec::with_capacity(self.length);
+		for bucket in &mut self.buckets {
+			let from = bucket.index;
+			items.extend(bucket.items.drain(..).map(|item| (from, item)));
+			bucket.top = None;
+		}
+
+		self.length = 0;
+		for (from, (key, val)) in items {
+			let key = shift(key).unwrap();
+			let to = self.bucket_index(key);
+			if let Some(observer) = &mut self.observer.0 { observer.relocated(key, &val, from, to); }
+			self.place(key, val, false).unwrap();
+		}
+
+		Ok(())
+	}
+
+	// moves the bound to "bound" and redistributes all items accordingly;
+	// returns the items below the new bound
+	fn rebase(&mut self, bound: u32) -> Vec<(u32, V)> {
+		if bound == self.toplast { return Vec::new(); }
+
+		let mut items: Vec<(usize, (u32, V))> = Vec::with_capacity(self.length);
+		for bucket in &mut self.buckets {
+			let from = bucket.index;
+			items.extend(bucket.items.drain(..).map(|item| (from, item)));
+			bucket.top = None;
+		}
+
+		self.toplast = bound;
+		self.length = 0;
+		self.generation += 1;
+
+		let mut parked = Vec::new();
+		for (from, (key, val)) in items {
+			if key < bound {
+				#[cfg(feature = "metrics")]
+				metrics::gauge!("radixheap_length").decrement(1.0);
+				if let Some(observer) = &mut self.observer.0 { observer.extracted(key, &val); }
+				parked.push((key, val));
+			} else {
+				let to = self.bucket_index(key);
+				if let Some(observer) = &mut self.observer.0 { observer.relocated(key, &val, from, to); }
+				self.place(key, val, false).unwrap();
+			}
+		}
+
+		parked
+	}
+
+	// removes all items with keys in "range" and returns them sorted by key;
+	// buckets lying within the range are drained as a whole. The bound is
+	// not affected.
+	pub fn pop_range<R: RangeBounds<u32>>(&mut self, range: R) -> Vec<(u32, V)> {
+		let mut removed: Vec<(u32, V)> = Vec::new();
+
+		for index in 0..self.buckets.len() {
+			if self.buckets[index].empty() { continue; }
+
+			let covered = self.bucket_range(index).is_some_and(|(low, high)| range.contains(&low) && range.contains(&high));
+			let bucket = &mut self.buckets[index];
+
+			if covered { removed.append(&mut bucket.items); } else {
+				let (hit, keep): (Vec<_>, Vec<_>) = std::mem::take(&mut bucket.items)
+					.into_iter().partition(|(k, _)| range.contains(k));
+				bucket.items = keep;
+				removed.extend(hit);
+			}
+
+			bucket.reset_top();
+		}
+
+		removed.sort_by_key(|&(k, _)| k);
+		self.length -= removed.len();
+		if !removed.is_empty() { self.generation += 1; }
+
+		if let Some(stats) = &mut self.stats { stats.pops += removed.len() as u64; }
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").decrement(removed.len() as f64);
+		if let Some(observer) = &mut self.observer.0 {
+			for (key, val) in &removed { observer.extracted(*key, val); }
+		}
+
+		removed
+	}
+
+	fn remove_at(&mut self, bucket: usize, position: usize) -> (u32, V) {
+		let (key, val) = self.buckets[bucket].remove(position);
+		self.length -= 1;
+		self.generation += 1;
+
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").decrement(1.0);
+		if let Some(observer) = &mut self.observer.0 { observer.extracted(key, &val); }
+		(key, val)
+	}
+
+	// reserves room for "additional" more items spread evenly over all
+	// buckets, like "with_capacity()", failing instead of aborting if the
+	// memory cannot be allocated
+	pub fn try_reserve(&mut self, additional: usize) -> Result<(), &'static str> {
+		let per_bucket = additional.div_ceil(self.buckets.len());
+
+		for bucket in &mut self.buckets {
+			bucket.items.try_reserve(per_bucket).map_err(|_| "allocation failed")?;
+		}
+
+		Ok(())
+	}
+
+	// same as "push", but fails instead of aborting if the bucket of the key
+	// cannot grow
+	pub fn try_push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key >= self.toplast {
+			let bucket = self.bucket_index(key);
+			self.buckets[bucket].items.try_reserve(1).map_err(|_| "allocation failed")?;
+		}
+
+		self.push(key, val)
+	}
+
+	// pushes all items using "try_push()"; the items pushed before a failure
+	// stay in the heap
+	pub fn try_extend<I: IntoIterator<Item = (u32, V)>>(&mut self, items: I) -> Result<(), &'static str> {
+		items.into_iter().try_for_each(|(key, val)| self.try_push(key, val))
+	}
+
+	// inserts a key/value pair into its bucket without counting it as
+	// a push, as done when redistributing a bucket
+	fn place(&mut self, key: u32, val: V, front: bool) -> Result<(), &'static str> {
+		let bucket = self.bucket_index(key);
+		if front && bucket == 0 && !self.buckets[0].empty() { self.generation += 1; }
+		self.bucket_mut(bucket).push(key, val, front)?;
+		self.length += 1;
+
+		let count = self.bucket_mut(bucket).length();
+		if let Some(stats) = &mut self.stats {
+			let high_water = Self::slot_mut(&mut stats.high_water, bucket);
+			if count > *high_water { *high_water = count; }
+		}
+
+		Ok(())
+	}
+
+	// the heap always holds 33 buckets and "high_water" one entry per
+	// bucket, while "bucket_index()" only returns indices from 0 to 32 and
+	// "pop()" only indices of existing buckets; so with the "unsafe-perf"
+	// feature these indices are not checked again
+	fn bucket_mut(&mut self, index: usize) -> &mut Bucket<V> { Self::slot_mut(&mut self.buckets, index) }
+
+	#[cfg(not(feature = "unsafe-perf"))]
+	fn slot_mut<T>(slots: &mut [T], index: usize) -> &mut T { &mut slots[index] }
+
+	#[cfg(feature = "unsafe-perf")]
+	fn slot_mut<T>(slots: &mut [T], index: usize) -> &mut T {
+		debug_assert!(index < slots.len());
+		// SAFETY: only called with bucket indices, see "bucket_mut()"
+		unsafe { slots.get_unchecked_mut(index) }
+	}
+
+	// bucket a key would be pushed into right now; "None" for keys the heap
+	// does not accept anymore
+	pub fn bucket_for_key(&self, key: u32) -> Option<usize> {
+		if key < self.toplast { None } else { Some(self.bucket_index(key)) }
+	}
+
+	pub fn bucket_view(&self, index: usize) -> Option<BucketView<'_, V>> {
+		let bucket = self.buckets.get(index)?;
+		Some(BucketView { bucket, range: self.bucket_range(index) })
+	}
+
+	pub fn bucket_views(&self) -> impl Iterator<Item = BucketView<'_, V>> {
+		(0..self.buckets.len()).filter_map(move |i| self.bucket_view(i))
+	}
+
+	// smallest entry of every non-empty bucket as "(bucket, key, value)", in
+	// ascending key order; nothing is popped or cloned
+	pub fn peek_bucket_tops(&self) -> impl Iterator<Item = (usize, u32, &V)> {
+		self.buckets.iter().filter_map(|b| b.top.as_ref().map(|(k, v)| (b.index, *k, v)))
+	}
+
+	// the "n" smallest entries in ascending key order, without removing or
+	// cloning them. All keys of a bucket lie below those of later buckets,
+	// so whole buckets are taken in turn and only the last one needed has
+	// to be partially selected from. Bucket 0 is listed in pop order.
+	pub fn n_smallest(&self, n: usize) -> Vec<(u32, &V)> {
+		let mut smallest: Vec<(u32, &V)> = Vec::with_capacity(n.min(self.length));
+
+		for bucket in self.buckets.iter().filter(|b| !b.empty()) {
+			let wanted = n - smallest.len();
+			if wanted == 0 { break; }
+
+			if bucket.index == 0 {
+				smallest.extend(bucket.items.iter().rev().take(wanted).map(|(k, v)| (*k, v)));
+				continue;
+			}
+
+			let mut items: Vec<(u32, &V)> = bucket.items.iter().map(|(k, v)| (*k, v)).collect();
+			if wanted < items.len() {
+				items.select_nth_unstable_by_key(wanted - 1, |&(k, _)| k);
+				items.truncate(wanted);
+			}
+
+			items.sort_by_key(|&(k, _)| k);
+			smallest.append(&mut items);
+		}
+
+		smallest
+	}
+
+	// key the item at position "k" (counting from 0) would be popped with,
+	// e.g. "k = len() * 95 / 100" for the deadline of the 95th percentile;
+	// buckets before the one holding it are skipped by their lengths
+	pub fn kth_smallest_key(&self, k: usize) -> Option<u32> {
+		let mut skipped = 0;
+
+		for bucket in self.buckets.iter() {
+			if k < skipped + bucket.length() {
+				if bucket.index == 0 { return Some(self.toplast); }
+
+				let mut keys: Vec<u32> = bucket.items.iter().map(|&(k, _)| k).collect();
+				return Some(*keys.select_nth_unstable(k - skipped).1);
+			}
+
+			skipped += bucket.length();
+		}
+
+		None
+	}
+
+	// number of stored keys per range of "width" keys, by first key of the
+	// range; buckets lying within a single range are counted as a whole
+	pub fn key_histogram(&self, width: u32) -> BTreeMap<u32, usize> {
+		assert!(width > 0, "histogram width must not be zero");
+		let mut histogram = BTreeMap::new();
+
+		for (index, bucket) in self.buckets.iter().enumerate().filter(|(_, b)| !b.empty()) {
+			match self.bucket_range(index) {
+				Some((low, high)) if low / width == high / width => {
+					*histogram.entry(low - low % width).or_insert(0) += bucket.length();
+				},
+				_ => for &(key, _) in &bucket.items { *histogram.entry(key - key % width).or_insert(0) += 1; }
+			}
+		}
+
+		histogram
+	}
+
+	// any key from "toplast" up to "u32::MAX" maps to one of the 33 buckets:
+	// the highest bit in which it differs from "toplast" lies between 0 and
+	// 31, and equal keys go into bucket 0
+	fn bucket_index(&self, key: u32) -> usize {
+		// convention
+		if key == self.toplast { 0 }
+			else { 32 - (key ^ self.toplast).leading_zeros() as usize }
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		if self.is_empty() { return None; }
+		if let Some(stats) = &mut self.stats { stats.pops += 1; }
+		self.generation += 1;
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").decrement(1.0);
+		if let Some(top) = self.pop_soft() { return Some(top); }
+
+		let mut top: Option<(u32, V)> = None;
+		let mut index: usize = 0;
+
+		#[allow(unused_mut)] // "bucket" needs to be mutable for "pop()"
+		for mut bucket in &mut self.buckets {
+			if !bucket.empty() {
+				if bucket.index == 0 {
+					#[cfg(feature = "tracing")]
+					tracing::trace!(key = self.toplast, bucket = 0, "pop");
+
+					self.length -= 1;
+					let top = bucket.pop();
+
+					if let (Some(observer), Some((k, v))) = (&mut self.observer.0, &top) {
+						observer.extracted(*k, v);
+					}
+
+					return top;
+				} else {
+					top = bucket.pop();
+
+					// most important line for restructuring
+					self.toplast = if let Some((k, _)) = top { k } else { return None; };
+					index = bucket.index;
+
+					// use first non-empty bucket for restructuring
+					break;
+				}
+			}
+		}
+
+		let bucket = self.bucket_mut(index);
+		let mut current = std::mem::take(&mut bucket.items);
+		bucket.top = None;
+
+		#[cfg(feature = "tracing")]
+		tracing::trace!(key = self.toplast, bucket = index, "pop");
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!("restructure", bucket = index, items = current.len()).entered();
+
+		// re-inserted items are already accounted for in "length"
+		self.length -= current.len();
+
+		if let Some(stats) = self.stats.as_mut().filter(|_| !current.is_empty()) {
+			stats.restructures += 1;
+			stats.moved += current.len() as u64;
+		}
+
+		#[cfg(feature = "metrics")]
+		if !current.is_empty() {
+			metrics::counter!("radixheap_restructures").increment(1);
+			metrics::counter!("radixheap_redistributed_items").increment(current.len() as u64);
+		}
+
+		if let (Some(observer), Some((k, v))) = (&mut self.observer.0, &top) { observer.extracted(*k, v); }
+
+		for (k, v) in current.drain(..) {
+			let to = self.bucket_index(k);
+			if let Some(observer) = &mut self.observer.0 { observer.relocated(k, &v, index, to); }
+
+			// uses updated bucket index for re-insertion:
+			self.place(k, v, false).unwrap();
+		}
+
+		// bucket 0 was empty before, so it now holds its items in insertion
+		// order and just needs to be turned around
+		if self.fifo_ties && !self.buckets[0].empty() {
+			self.buckets[0].items.reverse();
+			self.buckets[0].top = self.buckets[0].items.last().cloned();
+		}
+
+		// the original bucket must be empty after reorganizing the heap,
+		// so it can take its allocation back
+		let bucket = self.bucket_mut(index);
+		assert!(bucket.empty());
+		bucket.items = current;
+		self.length -= 1;
+		top
+	}
+
+	// hands out the last item of the first non-empty bucket if that one is
+	// covered by the soft mode; as the bound is left alone, all other items
+	// stay in their buckets
+	fn pop_soft(&mut self) -> Option<(u32, V)> {
+		let soft_bits = self.soft_bits;
+		let bucket = self.buckets.iter_mut().find(|b| !b.empty()).filter(|b| b.index > 0 && b.index <= soft_bits)?;
+		let (key, val) = bucket.items.pop().unwrap();
+
+		if bucket.top.as_ref().is_some_and(|(k, v)| *k == key && *v == val) {
+			bucket.top = bucket.iter().min_by_key(|(k, _)| k).cloned();
+		}
+
+		#[cfg(feature = "tracing")]
+		tracing::trace!(key, bucket = bucket.index, "pop");
+
+		self.length -= 1;
+		if let Some(observer) = &mut self.observer.0 { observer.extracted(key, &val); }
+		Some((key, val))
+	}
+
+	// pops items and hands them to "f" until the heap runs empty, the next
+	// key exceeds "until", or "budget" is used up; returns the number of
+	// items processed
+	pub fn process_until<F: FnMut(u32, V)>(&mut self, until: u32, budget: Duration, mut f: F) -> usize {
+		let start = Instant::now();
+		let mut count = 0usize;
+
+		while self.peek_key().is_some_and(|k| k <= until) && start.elapsed() < budget {
+			let (key, val) = self.pop().unwrap();
+			f(key, val);
+			count += 1;
+		}
+
+		count
+	}
+
+	// pops all items sharing the smallest key at once, in pop order
+	pub fn pop_group(&mut self) -> Option<(u32, Vec<V>)> {
+		let (key, first) = self.pop()?;
+		let mut group = vec![first];
+
+		group.extend(self.pop_while(|k, _| k == key).map(|(_, v)| v));
+		Some((key, group))
+	}
+
+	// lazily pops items as long as "predicate" holds, e.g. everything due
+	pub fn pop_while<P: FnMut(u32, &V) -> bool>(&mut self, predicate: P) -> PopWhile<'_, V, P> {
+		PopWhile { heap: self, predicate, done: false }
+	}
+
+	pub fn pop_for<F: FnMut(u32, V)>(&mut self, budget: Duration, f: F) -> usize {
+		self.process_until(u32::MAX, budget, f)
+	}
+
+	pub fn peek(&self) -> Option<(u32, V)> {
+		if self.is_empty() { return None; }
+
+		for bucket in &self.buckets {
+			if !bucket.empty() {
+				if let Some((ref key, ref val)) = bucket.top {
+					return Some((*key, val.clone())).clone();
+				} else { return None; }
+			}
+		}
+
+		None
+	}
+
+	// keys grow with the bucket index, so the largest key is found in the
+	// last non-empty bucket; among equal keys the item "pop()" would return
+	// last is chosen
+	pub fn peek_max(&self) -> Option<(u32, V)> {
+		let (bucket, position) = self.max_position()?;
+		Some(self.buckets[bucket].items[position].clone())
+	}
+
+	// removes the item with the largest key; the bound is not affected
+	pub fn pop_max(&mut self) -> Option<(u32, V)> {
+		let (bucket, position) = self.max_position()?;
+		if let Some(stats) = &mut self.stats { stats.pops += 1; }
+		Some(self.remove_at(bucket, position))
+	}
+
+	fn max_position(&self) -> Option<(usize, usize)> {
+		let bucket = self.buckets.iter().rposition(|b| !b.empty())?;
+		if bucket == 0 { return Some((0, 0)); }
+
+		let items = self.buckets[bucket].items.iter().enumerate();
+		items.max_by_key(|(_, (k, _))| *k).map(|(position, _)| (bucket, position))
+	}
+
+	// smallest key without cloning the value belonging to it
+	pub fn peek_key(&self) -> Option<u32> { self.top().map(|(k, _)| *k) }
+
+	pub fn peek_value(&self) -> Option<&V> { self.top().map(|(_, v)| v) }
+
+	fn top(&self) -> Option<&(u32, V)> {
+		self.buckets.iter().find(|b| !b.empty()).and_then(|b| b.top.as_ref())
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn capacity(&self) -> usize {
+		self.buckets.iter().fold(0usize, |cap, b| { cap + b.capacity() })
+	}
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	#[deprecated(note = "use \"len()\"")]
+	pub fn length(&self) -> usize { self.len() }
+
+	#[deprecated(note = "use \"is_empty()\"")]
+	pub fn empty(&self) -> bool { self.is_empty() }
+
+	// smallest key "push" currently accepts
+	pub fn bound(&self) -> u32 { self.toplast }
+
+	// removes all items but keeps the memory of all buckets for reuse, so
+	// refilling the heap does not allocate again; the bound is kept as well
+	pub fn clear(&mut self) {
+		self.buckets.iter_mut().all(|b| {
+			b.clear();
+			true
+		});
+
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").decrement(self.length as f64);
+		if let Some(observer) = &mut self.observer.0 { observer.cleared(); }
+		self.length = 0usize;
+		self.generation += 1;
+	}
+
+	// removes all items and releases the memory held by the buckets
+	pub fn clear_shrink(&mut self) {
+		self.clear();
+		for bucket in &mut self.buckets { bucket.items.shrink_to_fit(); }
+	}
+
+	// checks all internal invariants and describes every violation found
+	pub fn validate(&self) -> Result<(), Vec<String>> {
+		let mut errors = Vec::new();
+
+		if self.buckets.len() != 33 {
+			errors.push(format!("expected 33 buckets, found {}", self.buckets.len()));
+		}
+
+		for (i, bucket) in self.buckets.iter().enumerate() {
+			if bucket.index != i { errors.push(format!("bucket {} claims index {}", i, bucket.index)); }
+
+			for &(key, _) in bucket.iter() {
+				if key < self.toplast {
+					errors.push(format!("bucket {}: key {} below bound {}", i, key, self.toplast));
+				} else if self.bucket_index(key) != i {
+					errors.push(format!("bucket {}: key {} belongs into bucket {}", i, key, self.bucket_index(key)));
+				}
+			}
+
+			let expected = if i == 0 { bucket.items.last() }
+				else { bucket.iter().min_by_key(|(k, _)| k) };
+
+			match (&bucket.top, expected) {
+				(None, None) => {},
+				(Some((k, _)), Some(&(e, _))) if *k == e => {},
+				(top, expected) => errors.push(format!("bucket {}: priority element {:?}, expected {:?}",
+				                                       i, top.as_ref().map(|t| t.0), expected.map(|e| e.0)))
+			}
+		}
+
+		let count: usize = self.buckets.iter().map(|b| b.length()).sum();
+		if count != self.length {
+			errors.push(format!("length is {}, but buckets hold {} items", self.length, count));
+		}
+
+		if errors.is_empty() { Ok(()) } else { Err(errors) }
+	}
+
+	// range of keys bucket "index" can currently hold; buckets unreachable
+	// from the current bound have none
+	fn bucket_range(&self, index: usize) -> Option<(u32, u32)> {
+		if index == 0 { return Some((self.toplast, self.toplast)); }
+
+		let bit = 1u64 << (index - 1);
+		if u64::from(self.toplast) & bit != 0 { return None; }
+
+		let low = (u64::from(self.toplast) >> index << index) | bit;
+		Some((low as u32, (low | (bit - 1)) as u32))
+	}
+
+	// renders the buckets, their key ranges and contents as a Graphviz
+	// graph; the priority element of every bucket is marked with "*"
+	pub fn to_dot(&self) -> String {
+		fn escape(text: &str) -> String {
+			text.chars().fold(String::new(), |mut out, c| {
+				if "{}|<>\"\\".contains(c) { out.push('\\'); }
+				out.push(c);
+				out
+			})
+		}
+
+		let mut dot = String::from("digraph radixheap {\n\trankdir=LR;\n\tnode [shape=record];\n");
+		dot.push_str(&format!("\tbound [shape=plaintext, label=\"bound: {}\\nlength: {}\"];\n",
+		                      self.toplast, self.length));
+
+		for bucket in &self.buckets {
+			let range = match self.bucket_range(bucket.index) {
+				Some((low, high)) => format!("[{}, {}]", low, high),
+				None => String::from("unreachable")
+			};
+
+			let mut label = format!("bucket {}|{}", bucket.index, range);
+			let top = bucket.top.as_ref().map(|t| t.0);
+
+			for (key, val) in bucket.iter() {
+				let mark = if Some(*key) == top { "*" } else { "" };
+				label.push_str(&format!("|{}{}: {}", mark, key, escape(&format!("{:?}", val))));
+			}
+
+			dot.push_str(&format!("\tb{} [label=\"{{{}}}\"];\n", bucket.index, label));
+		}
+
+		dot.push_str("\tbound -> b0");
+		for index in 1..self.buckets.len() { dot.push_str(&format!(" -> b{}", index)); }
+		dot.push_str(";\n}\n");
+		dot
+	}
+
+	// items of every bucket in bucket order, exposing the exact layout
+	pub(crate) fn raw_buckets(&self) -> (u32, Vec<&[(u32, V)]>) {
+		(self.toplast, self.buckets.iter().map(|b| b.items.as_slice()).collect())
+	}
+
+	// inverse of "raw_buckets()"; fails unless every key sits in the bucket
+	// the given bound assigns it to
+	pub(crate) fn from_raw_buckets(toplast: u32, buckets: Vec<Vec<(u32, V)>>) -> Result<RadixHeap<V>, &'static str> {
+		let mut heap = RadixHeap::with_bound(toplast);
+		if buckets.len() != heap.buckets.len() { return Err("wrong number of buckets"); }
+
+		for (index, items) in buckets.into_iter().enumerate() {
+			for (key, val) in items { heap.place_raw(index, key, val)?; }
+		}
+
+		Ok(heap)
+	}
+
+	// empty heap rebuilt item by item with "place_raw()"
+	pub(crate) fn with_bound(toplast: u32) -> RadixHeap<V> {
+		let mut heap = RadixHeap::new();
+		heap.toplast = toplast;
+		heap
+	}
+
+	// appends an item to the given bucket, keeping the layout it is read in
+	pub(crate) fn place_raw(&mut self, index: usize, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.toplast || self.bucket_index(key) != index { return Err("key in wrong bucket"); }
+		self.place(key, val, false)
+	}
+
+	// every stored value, including the copies buckets keep of their
+	// priority elements
+	pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+		self.buckets.iter_mut().flat_map(|b| b.items.iter_mut().chain(b.top.as_mut()).map(|(_, v)| v))
+	}
+
+	// replaces the observer of the heap, returning the previous one
+	pub fn set_observer<O: Observer<V> + Send + Sync + 'static>(&mut self, observer: O)
+		-> Option<Box<dyn Observer<V> + Send + Sync>> {
+		self.observer.0.replace(Box::new(observer))
+	}
+
+	pub fn take_observer(&mut self) -> Option<Box<dyn Observer<V> + Send + Sync>> {
+		self.observer.0.take()
+	}
+
+	// starts collecting statistics; keeps the counters if already enabled
+	pub fn enable_stats(&mut self) {
+		if self.stats.is_some() { return; }
+
+		let high_water = self.buckets.iter().map(|b| b.length()).collect();
+		self.stats = Some(Box::new(Stats {
+			pushes: 0, pops: 0, rejected: 0, restructures: 0, moved: 0, high_water
+		}));
+	}
+
+	pub fn disable_stats(&mut self) { self.stats = None; }
+
+	pub fn stats(&self) -> Option<&Stats> { self.stats.as_deref() }
+
+	pub fn reset_stats(&mut self) {
+		if self.stats.take().is_some() { self.enable_stats(); }
+	}
+
+	// high-water marks observed so far; "None" unless statistics are enabled
+	pub fn tuning_profile(&self) -> Option<TuningProfile> {
+		self.stats.as_ref().map(|stats| TuningProfile { capacities: stats.high_water.clone() })
+	}
+
+	// grows every bucket to the capacity given by "profile", e.g. after
+	// "clear_shrink()"; buckets are never shrunk
+	pub fn apply_profile(&mut self, profile: &TuningProfile) {
+		for (bucket, &capacity) in self.buckets.iter_mut().zip(&profile.capacities) {
+			bucket.items.reserve(capacity.saturating_sub(bucket.items.len()));
+		}
+	}
+
+	pub fn cursor(&self) -> Cursor { Cursor { generation: self.generation, bucket: 0, item: 0, behind: 0 } }
+
+	pub fn bucket_iter(&self) -> RadixBucketIter<'_, V> {
+		RadixBucketIter { container: self, index: 0 }
+	}
+
+	// all items in a deterministic order: by bucket, and within a bucket in
+	// the order they were placed there (reversed for bucket 0 if "fifo_ties"
+	// is set); restructuring only changes the position of the items moved
+	pub fn tuples(&self) -> Vec<(u32, V)> {
+		self.bucket_iter().flat_map(|b| b.items.clone()).collect()
+	}
+
+	// sorted by key, equal keys keep the order of "tuples()"
+	pub fn sorted_tuples(&self) -> Vec<(u32, V)> {
+		let mut coll = self.tuples();
+		coll.sort_by_key(|&(k, _)| k);
+		coll
+	}
+
+	// read-only form sorted by key, see "FrozenRadixHeap::thaw()" for the
+	// way back
+	pub fn freeze(mut self) -> FrozenRadixHeap<V> {
+		let mut items = Vec::with_capacity(self.length);
+		for bucket in self.buckets.iter_mut().rev() { items.append(&mut bucket.items); }
+
+		items.sort_by_key(|&(k, _)| k);
+		FrozenRadixHeap::from_sorted(items, self.toplast)
+	}
+
+	pub fn keys(&self) -> Vec<u32> {
+		self.sorted_tuples().into_iter().map(|(k, _)| k).collect()
+	}
+
+	pub fn values(&self) -> Vec<V> {
+		self.sorted_tuples().into_iter().map(|(_, v)| v.clone()).collect()
+	}
+}
+
+impl<V: Clone + Debug + Ord> RadixHeap<V> {
+	// all items ordered by key and then by value, which is the same for
+	// heaps holding the same items no matter how they are laid out
+	fn canonical(&self) -> Vec<&(u32, V)> {
+		let mut items: Vec<&(u32, V)> = self.buckets.iter().flat_map(|b| b.items.iter()).collect();
+		items.sort_unstable();
+		items
+	}
+}
+
+// two heaps are equal if they share their bound and hold the same multiset
+// of items; layout, statistics and settings are not compared
+impl<V: Clone + Debug + Ord> PartialEq for RadixHeap<V> {
+	fn eq(&self, other: &RadixHeap<V>) -> bool {
+		self.toplast == other.toplast && self.length == other.length && self.canonical() == other.canonical()
+	}
+}
+
+impl<V: Clone + Debug + Ord> Eq for RadixHeap<V> {}
+
+impl<V: Clone + Debug + Ord + Hash> Hash for RadixHeap<V> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.toplast.hash(state);
+		self.canonical().hash(state);
+	}
+}
+
+// the "radixheap_length" gauge sums up the lengths of all heaps, so it
+// has to follow clones and drops as well
+impl<V: Clone + Debug + Ord> Clone for RadixHeap<V> {
+	fn clone(&self) -> RadixHeap<V> {
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").increment(self.length as f64);
+
+		RadixHeap {
+			buckets: self.buckets.clone(),
+			toplast: self.toplast,
+			length: self.length,
+			stats: self.stats.clone(),
+			observer: ObserverSlot(None),
+			fifo_ties: self.fifo_ties,
+			generation: self.generation,
+			soft_bits: self.soft_bits
+		}
+	}
+
+	// reuses the bucket allocations of "self" instead of allocating anew
+	fn clone_from(&mut self, source: &RadixHeap<V>) {
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").increment(source.length as f64 - self.length as f64);
+
+		for (bucket, other) in self.buckets.iter_mut().zip(&source.buckets) {
+			bucket.items.clone_from(&other.items);
+			bucket.top.clone_from(&other.top);
+		}
+
+		self.toplast = source.toplast;
+		self.length = source.length;
+		self.stats.clone_from(&source.stats);
+		self.observer = ObserverSlot(None);
+		self.fifo_ties = source.fifo_ties;
+		self.soft_bits = source.soft_bits;
+		self.generation += 1;
+	}
+}
+
+#[cfg(feature = "metrics")]
+impl<V: Debug + Ord> Drop for RadixHeap<V> {
+	fn drop(&mut self) { metrics::gauge!("radixheap_length").decrement(self.length as f64); }
+}
+
+impl<V: Clone + Debug + Ord> RadixHeapBuilder<V> {
+	// number of items to reserve room for, see "RadixHeap::with_capacity()"
+	pub fn capacity(mut self, capacity: usize) -> RadixHeapBuilder<V> {
+		self.capacity = capacity;
+		self
+	}
+
+	// pre-sizes the buckets as learned by an earlier heap
+	pub fn profile(mut self, profile: TuningProfile) -> RadixHeapBuilder<V> {
+		self.profile = Some(profile);
+		self
+	}
+
+	// pops items with equal keys in the order they were pushed instead of
+	// the reverse; pushing a key equal to the last popped one then takes
+	// time linear in the number of such keys
+	pub fn fifo_ties(mut self, fifo: bool) -> RadixHeapBuilder<V> {
+		self.fifo_ties = fifo;
+		self
+	}
+
+	pub fn stats(mut self, stats: bool) -> RadixHeapBuilder<V> {
+		self.stats = stats;
+		self
+	}
+
+	// approximate mode: a pop may return any item whose key is less than
+	// "2^(bits - 1)" above the smallest one, and saves restructuring the
+	// buckets in exchange; popped keys may then go down by as much
+	pub fn soft(mut self, bits: u32) -> RadixHeapBuilder<V> {
+		self.soft_bits = bits.min(32) as usize;
+		self
+	}
+
+	pub fn build(self) -> RadixHeap<V> {
+		let mut heap = RadixHeap::with_capacity(self.capacity);
+		heap.fifo_ties = self.fifo_ties;
+		heap.soft_bits = self.soft_bits;
+		if self.stats { heap.enable_stats(); }
+		if let Some(profile) = &self.profile { heap.apply_profile(profile); }
+		heap
+	}
+}
+
+impl<V: Clone + Debug + Ord> Default for RadixHeap<V> {
+	fn default() -> RadixHeap<V> { RadixHeap::new() }
+}
+
+#[cfg(feature = "rayon")]
+impl<V: Clone + Debug + Ord + Send + Sync> ParallelExtend<(u32, V)> for RadixHeap<V> {
+	// panics if any of the keys is smaller than the key extracted last
+	fn par_extend<I: IntoParallelIterator<Item = (u32, V)>>(&mut self, par_iter: I) {
+		let toplast = self.toplast;
+		let count = self.buckets.len();
+
+		// partition the input by bucket in parallel
+		let parts: Vec<Vec<(u32, V)>> = par_iter.into_par_iter()
+			.fold(|| vec![Vec::new(); count], |mut parts, (key, val)| {
+				assert!(key >= toplast, "key too small");
+				parts[self.bucket_index(key)].push((key, val));
+				parts
+			})
+			.reduce(|| vec![Vec::new(); count], |mut left, right| {
+				for (l, r) in left.iter_mut().zip(right) { l.extend(r); }
+				left
+			});
+
+		let count = parts.iter().map(|p| p.len()).sum::<usize>();
+		self.length += count;
+		if let Some(stats) = &mut self.stats { stats.pushes += count as u64; }
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").increment(count as f64);
+
+		if let Some(observer) = &mut self.observer.0 {
+			for (bucket, part) in parts.iter().enumerate() {
+				for (key, val) in part { observer.inserted(*key, val, bucket); }
+			}
+		}
+
+		let fifo_ties = self.fifo_ties;
+		if fifo_ties && !parts[0].is_empty() { self.generation += 1; }
+		self.buckets.par_iter_mut().zip(parts).for_each(|(bucket, mut part)| {
+			if part.is_empty() { return; }
+
+			if bucket.index == 0 && fifo_ties {
+				part.reverse();
+				part.append(&mut bucket.items);
+				bucket.items = part;
+			} else { bucket.items.extend(part); }
+
+			// same priority element "push" would have chosen
+			bucket.top = if bucket.index == 0 { bucket.items.last().cloned() }
+				else { bucket.iter().min_by_key(|(k, _)| k).cloned() };
+		});
+
+		if let Some(stats) = &mut self.stats {
+			for (mark, bucket) in stats.high_water.iter_mut().zip(&self.buckets) {
+				*mark = (*mark).max(bucket.length());
+			}
+		}
+	}
+}
+
+#[cfg(feature = "rayon")]
+impl<V: Clone + Debug + Ord + Send + Sync> RadixHeap<V> {
+	// same result as popping all items, but sorted in parallel
+	pub fn par_drain_sorted(&mut self) -> Vec<(u32, V)> {
+		let mut items: Vec<(u32, V)> = Vec::with_capacity(self.length);
+
+		for bucket in &mut self.buckets {
+			items.append(&mut bucket.items);
+			bucket.top = None;
+		}
+
+		items.par_sort_by_key(|&(k, _)| k);
+		self.generation += 1;
+
+		// the heap behaves as if the last item was popped regularly
+		if let Some(&(k, _)) = items.last() { self.toplast = k; }
+		if let Some(stats) = &mut self.stats { stats.pops += items.len() as u64; }
+		if let Some(observer) = &mut self.observer.0 {
+			for (key, val) in &items { observer.extracted(*key, val); }
+		}
+		#[cfg(feature = "metrics")]
+		metrics::gauge!("radixheap_length").decrement(self.length as f64);
+		self.length = 0;
+		items
+	}
+}
+
+#[cfg(feature = "rayon")]
+impl<V: Clone + Debug + Ord + Send + Sync> FromParallelIterator<(u32, V)> for RadixHeap<V> {
+	fn from_par_iter<I: IntoParallelIterator<Item = (u32, V)>>(par_iter: I) -> RadixHeap<V> {
+		let mut heap = RadixHeap::new();
+		heap.par_extend(par_iter);
+		heap
+	}
+}
+
+// only a summary of the buckets is logged, so values need not implement
+// "Format"; errors are plain "&'static str"s, which do already
+#[cfg(feature = "defmt")]
+impl<V: Clone + Debug + Ord> defmt::Format for RadixHeap<V> {
+	fn format(&self, f: defmt::Formatter) {
+		defmt::write!(f, "RadixHeap {{ len: {=usize}, bound: {=u32}, buckets: [", self.length, self.toplast);
+		for bucket in self.buckets.iter().filter(|b| !b.empty()) {
+			defmt::write!(f, " {=usize}: {=usize}", bucket.index, bucket.length());
+		}
+		defmt::write!(f, " ] }}");
+	}
+}
+
+#[cfg(feature = "defmt")]
+#[allow(dead_code)]
+const _: () = {
+	fn format<T: defmt::Format>() {}
+
+	fn heap_is_format<V: Clone + Debug + Ord>() {
+		format::<RadixHeap<V>>();
+		format::<BucketView<'_, V>>();
+		format::<Stats>();
+		format::<TuningProfile>();
+		format::<Result<(), &'static str>>();
+	}
+};
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_heap() {
+		let mut heap = RadixHeap::default();
+		assert!(heap.is_empty());
+		assert_eq!(heap.length, 0);
+		heap.push(7, 'a').unwrap();
+		assert_eq!(heap.length, 1);
+		heap.push(2, 'b').unwrap();
+		heap.push(9, 'c').unwrap();
+
+		assert_eq!(heap.peek(), Some((2, 'b')));
+		assert_eq!(heap.pop(), Some((2, 'b')));
+		assert_eq!(heap.toplast, 2);
+		assert_eq!(heap.pop(), Some((7, 'a')));
+		assert_eq!(heap.toplast, 7);
+		assert_eq!(heap.pop(), Some((9, 'c')));
+		assert_eq!(heap.toplast, 9);
+		assert!(heap.is_empty());
+	}
+
+	#[test]
+	fn test_pop() {
+		let mut heap = RadixHeap::new();
+		let mut rng = rand::thread_rng();
+		let mut keys: Vec<u32> = Vec::with_capacity(100);
+
+		for _ in 0..100 {
+			let number: u32 = rng.gen();
+			keys.push(number);
+		}
+
+		keys.sort_by(|a, b| b.cmp(a));
+
+		assert_eq!(heap.capacity(), 0usize);
+
+		for _ in 0..100 {
+			let number: u32 = keys.pop().unwrap();
+			heap.push(number, "").unwrap_or_else(|s| {
+				panic!("failed to push key {}: {}", number, s);
+			});
+			assert_eq!(heap.peek(), Some((number, "")));
+			heap.pop();
+		}
+
+		assert!(heap.is_empty());
+		heap.clear();
+		assert!(heap.is_empty());
+	}
+
+	#[test]
+	fn test_duplicates() {
+		let mut heap = RadixHeap::new();
+		heap.push(5, 'a').unwrap();
+		heap.push(5, 'b').unwrap();
+		heap.push(6, 'c').unwrap();
+
+		assert_eq!(heap.pop(), Some((5, 'a')));
+		assert_eq!(heap.peek(), Some((5, 'b')));
+		assert_eq!(heap.pop(), Some((5, 'b')));
+		assert_eq!(heap.pop(), Some((6, 'c')));
+		assert_eq!(heap.pop(), None);
+	}
+
+	#[test]
+	fn test_length() {
+		let mut heap = RadixHeap::new();
+		for key in &[5, 9, 12, 13, 16, 45] { heap.push(*key, ()).unwrap(); }
+
+		for length in (0..6).rev() {
+			assert!(heap.pop().is_some());
+			assert_eq!(heap.len(), length);
+			assert_eq!(heap.tuples().len(), length);
+		}
+
+		assert_eq!(heap.pop(), None);
+		assert!(heap.is_empty());
+
+		#[allow(deprecated)]
+		{ assert_eq!((heap.length(), heap.empty()), (0, true)); }
+	}
+
+	#[test]
+	#[cfg(feature = "rayon")]
+	fn test_par_extend() {
+		use rayon::iter::IntoParallelIterator;
+
+		let mut heap: RadixHeap<u32> = (0..10000u32).into_par_iter()
+			.map(|i| ((i * 7919) % 10007, i))
+			.collect();
+		assert_eq!(heap.len(), 10000);
+
+		heap.pop();
+		heap.par_extend(vec![(20000, 0), (20001, 1)]);
+		assert_eq!(heap.len(), 10001);
+
+		let mut last = 0;
+		while let Some((key, _)) = heap.pop() {
+			assert!(key >= last);
+			last = key;
+		}
+		assert_eq!(last, 20001);
+	}
+
+	#[test]
+	#[cfg(feature = "rayon")]
+	fn test_par_drain_sorted() {
+		let mut heap = RadixHeap::new();
+		for key in &[90, 4, 77, 4, 1000, 12] { heap.push(*key, *key % 7).unwrap(); }
+
+		assert_eq!(heap.par_drain_sorted().into_iter().map(|(k, _)| k).collect::<Vec<u32>>(),
+		           vec![4, 4, 12, 77, 90, 1000]);
+		assert!(heap.is_empty());
+		assert!(heap.push(999, 0).is_err());
+		assert!(heap.push(1000, 0).is_ok());
+	}
+
+	#[test]
+	fn test_send() {
+		use std::cell::RefCell;
+		use std::sync::{Arc, Mutex};
+		use std::thread;
+
+		// "RefCell" is "Send" but not "Sync"
+		let mut heap: RadixHeap<RefCell<u32>> = RadixHeap::new();
+		heap.push(3, RefCell::new(3)).unwrap();
+
+		let heap = thread::spawn(move || {
+			heap.push(1, RefCell::new(1)).unwrap();
+			heap
+		}).join().unwrap();
+		assert_eq!(heap.len(), 2);
+
+		let shared = Arc::new(Mutex::new(heap));
+		let workers: Vec<_> = (0..4u32).map(|i| {
+			let shared = Arc::clone(&shared);
+			thread::spawn(move || shared.lock().unwrap().push(10 + i, RefCell::new(i)).unwrap())
+		}).collect();
+
+		for worker in workers { worker.join().unwrap(); }
+		assert_eq!(shared.lock().unwrap().len(), 6);
+		assert_eq!(shared.lock().unwrap().pop(), Some((1, RefCell::new(1))));
+	}
+
+	#[test]
+	fn test_validate() {
+		let mut heap = RadixHeap::new();
+		for key in &[5, 17, 17, 300, 64] { heap.push(*key, *key).unwrap(); }
+		assert_eq!(heap.validate(), Ok(()));
+
+		while heap.pop().is_some() { assert_eq!(heap.validate(), Ok(())); }
+
+		heap.push(301, 1).unwrap();
+		heap.buckets[1].items.push((400, 2));
+		heap.buckets[3].items.push((17, 3));
+		heap.length = 5;
+
+		let errors = heap.validate().unwrap_err();
+		assert_eq!(errors.len(), 4);
+		assert_eq!(errors[0], "bucket 1: key 400 belongs into bucket 8");
+	}
+
+	#[test]
+	fn test_to_dot() {
+		let mut heap = RadixHeap::new();
+		heap.push(4, "a").unwrap();
+		heap.push(6, "b").unwrap();
+		heap.push(5, "{c}").unwrap();
+		heap.pop();
+
+		let dot = heap.to_dot();
+		assert!(dot.starts_with("digraph radixheap {"));
+		assert!(dot.contains("\tbound [shape=plaintext, label=\"bound: 4\\nlength: 2\"];"));
+		assert!(dot.contains("\tb1 [label=\"{bucket 1|[5, 5]|*5: \\\"\\{c\\}\\\"}\"];"));
+		assert!(dot.contains("\tb2 [label=\"{bucket 2|[6, 7]|*6: \\\"b\\\"}\"];"));
+		assert!(dot.contains("\tb3 [label=\"{bucket 3|unreachable}\"];"));
+		assert!(dot.contains("\tb32 [label=\"{bucket 32|[2147483648, 4294967295]}\"];"));
+		assert_eq!(RadixHeap::<u8>::new().bucket_range(32), Some((1 << 31, u32::MAX)));
+	}
+
+	#[test]
+	fn test_observer() {
+		use std::collections::HashMap;
+		use std::sync::{Arc, Mutex};
+
+		// external index of the bucket every value currently sits in
+		struct Index(Arc<Mutex<HashMap<char, usize>>>);
+
+		impl Observer<char> for Index {
+			fn inserted(&mut self, _: u32, val: &char, bucket: usize) {
+				self.0.lock().unwrap().insert(*val, bucket);
+			}
+			fn extracted(&mut self, _: u32, val: &char) { self.0.lock().unwrap().remove(val); }
+			fn relocated(&mut self, _: u32, val: &char, from: usize, to: usize) {
+				assert_eq!(self.0.lock().unwrap().insert(*val, to), Some(from));
+			}
+			fn cleared(&mut self) { self.0.lock().unwrap().clear(); }
+		}
+
+		let index = Arc::new(Mutex::new(HashMap::new()));
+		let mut heap = RadixHeap::new();
+		assert!(heap.set_observer(Index(Arc::clone(&index))).is_none());
+
+		for (key, val) in [(4, 'a'), (6, 'b'), (5, 'c'), (4, 'd')] { heap.push(key, val).unwrap(); }
+		assert_eq!(index.lock().unwrap()[&'b'], 3);
+
+		heap.pop();
+		assert_eq!(index.lock().unwrap().len(), 3);
+		assert_eq!(index.lock().unwrap()[&'b'], 2);
+		assert_eq!(index.lock().unwrap()[&'c'], 1);
+
+		// clones do not share the observer
+		heap.clone().clear();
+		assert_eq!(index.lock().unwrap().len(), 3);
+
+		heap.clear();
+		assert!(index.lock().unwrap().is_empty());
+		assert!(heap.take_observer().is_some());
+	}
+
+	#[test]
+	fn test_stats() {
+		let mut heap = RadixHeap::new();
+		heap.push(1, ()).unwrap();
+		assert_eq!(heap.stats(), None);
+
+		heap.enable_stats();
+		for key in &[8, 9, 10, 12] { heap.push(*key, ()).unwrap(); }
+		assert_eq!(heap.pop(), Some((1, ())));
+		assert_eq!(heap.pop(), Some((8, ())));
+		assert!(heap.push(2, ()).is_err());
+
+		let stats = heap.stats().unwrap();
+		assert_eq!((stats.pushes, stats.pops, stats.rejected), (4, 2, 1));
+		assert_eq!((stats.restructures, stats.moved), (1, 3));
+		assert_eq!(stats.high_water[4], 4);
+		assert_eq!(stats.high_water[1], 1);
+
+		heap.reset_stats();
+		assert_eq!(heap.stats().unwrap().pops, 0);
+		assert_eq!(heap.stats().unwrap().high_water[4], 0);
+		assert_eq!(heap.stats().unwrap().high_water[2], 1);
+	}
+
+	#[test]
+	fn test_tuning_profile() {
+		let mut heap = RadixHeap::builder().stats(true).build();
+		assert_eq!(RadixHeap::<u32>::new().tuning_profile(), None);
+
+		for key in 0..100 { heap.push(key, key).unwrap(); }
+		let mut profile = heap.tuning_profile().unwrap();
+		assert_eq!(profile.capacities()[7], 36);
+
+		profile.merge(&TuningProfile { capacities: vec![50] });
+		assert_eq!(profile.capacities()[..2], [50, 1]);
+
+		let tuned: RadixHeap<u32> = RadixHeap::builder().profile(profile).build();
+		assert!(tuned.bucket_view(7).unwrap().capacity() >= 36);
+
+		heap.clear_shrink();
+		heap.apply_profile(&heap.tuning_profile().unwrap());
+		assert!(heap.bucket_view(0).unwrap().capacity() >= 1);
+		assert!(heap.bucket_view(7).unwrap().capacity() >= 36);
+	}
+
+	#[cfg(feature = "tracing")]
+	#[test]
+	fn test_tracing() {
+		use std::sync::{Arc, Mutex};
+		use tracing::{Event, Metadata, Subscriber};
+		use tracing::span::{Attributes, Id, Record};
+
+		// records the names of all spans and events
+		struct Recorder(Arc<Mutex<Vec<String>>>);
+
+		impl Subscriber for Recorder {
+			fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+			fn new_span(&self, span: &Attributes<'_>) -> Id {
+				self.0.lock().unwrap().push(span.metadata().name().to_string());
+				Id::from_u64(1)
+			}
+			fn record(&self, _: &Id, _: &Record<'_>) {}
+			fn record_follows_from(&self, _: &Id, _: &Id) {}
+			fn event(&self, event: &Event<'_>) {
+				let mut message = String::new();
+				event.record(&mut |field: &tracing::field::Field, value: &dyn Debug| {
+					if field.name() == "message" { message = format!("{:?}", value); }
+				});
+				self.0.lock().unwrap().push(message);
+			}
+			fn enter(&self, _: &Id) {}
+			fn exit(&self, _: &Id) {}
+		}
+
+		let names = Arc::new(Mutex::new(Vec::new()));
+		tracing::subscriber::with_default(Recorder(Arc::clone(&names)), || {
+			let mut heap = RadixHeap::new();
+			heap.push(3, ()).unwrap();
+			heap.push(3, ()).unwrap();
+			heap.pop();
+			heap.pop();
+			assert!(heap.push(1, ()).is_err());
+		});
+
+		assert_eq!(*names.lock().unwrap(), vec!["push", "push", "pop", "restructure", "pop", "rejected push"]);
+	}
+
+	#[cfg(feature = "metrics")]
+	#[test]
+	fn test_metrics() {
+		use std::collections::HashMap;
+		use std::sync::{Arc, Mutex};
+		use std::sync::atomic::{AtomicU64, Ordering};
+		use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+		// keeps every counter and gauge in an atomic, gauges as "f64" bits
+		#[derive(Default)]
+		struct Registry(Mutex<HashMap<String, Arc<AtomicU64>>>);
+
+		impl Registry {
+			fn get(&self, key: &str) -> Arc<AtomicU64> {
+				Arc::clone(self.0.lock().unwrap().entry(key.to_string()).or_default())
+			}
+		}
+
+		impl Recorder for Registry {
+			fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+			fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+			fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+			fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+				Counter::from_arc(self.get(key.name()))
+			}
+			fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+				Gauge::from_arc(self.get(key.name()))
+			}
+			fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram { Histogram::noop() }
+		}
+
+		let registry = Registry::default();
+		let length = || f64::from_bits(registry.get("radixheap_length").load(Ordering::SeqCst));
+
+		metrics::with_local_recorder(&registry, || {
+			let mut heap = RadixHeap::new();
+			for key in &[1, 8, 9, 10] { heap.push(*key, ()).unwrap(); }
+			heap.pop();
+			heap.pop();
+			assert_eq!(length(), 2.0);
+
+			let copy = heap.clone();
+			heap.clear();
+			heap.push(12, ()).unwrap();
+			assert_eq!(length(), 3.0);
+
+			drop(copy);
+			assert_eq!(length(), 1.0);
+			assert_eq!(registry.get("radixheap_restructures").load(Ordering::SeqCst), 1);
+			assert_eq!(registry.get("radixheap_redistributed_items").load(Ordering::SeqCst), 2);
+
+			// approximate pops count as well
+			let mut soft = RadixHeap::builder().soft(4).build();
+			for key in [3, 1, 2, 7, 5, 40, 6] { soft.push(key, ()).unwrap(); }
+			while soft.pop().is_some() {}
+			assert_eq!(length(), 1.0);
+		});
+
+		assert_eq!(length(), 0.0);
+	}
+
+	#[test]
+	fn test_builder() {
+		let mut lifo = RadixHeap::builder().capacity(4).build();
+		let mut fifo = RadixHeap::builder().capacity(4).fifo_ties(true).stats(true).build();
+		assert!(lifo.stats().is_none());
+		assert!(fifo.capacity() >= 4);
+
+		for heap in [&mut lifo, &mut fifo] {
+			for (key, val) in [(3, 'x'), (5, 'a'), (5, 'b'), (5, 'c')] { heap.push(key, val).unwrap(); }
+			heap.pop();
+			heap.pop();
+			heap.push(5, 'd').unwrap();
+		}
+
+		let order = |heap: &mut RadixHeap<char>| std::iter::from_fn(|| heap.pop().map(|(_, v)| v)).collect::<String>();
+		assert_eq!(order(&mut lifo), "dcb");
+		assert_eq!(order(&mut fifo), "bcd");
+		assert_eq!(fifo.stats().unwrap().pops, 5);
+	}
+
+	#[test]
+	fn test_bucket_views() {
+		let mut heap = RadixHeap::new();
+		for key in &[4, 6, 5, 7, 200] { heap.push(*key, *key).unwrap(); }
+		heap.pop();
+
+		assert_eq!(heap.bucket_for_key(3), None);
+		assert_eq!(heap.bucket_for_key(4), Some(0));
+		assert_eq!(heap.bucket_for_key(6), Some(2));
+
+		let view = heap.bucket_view(2).unwrap();
+		assert_eq!((view.index(), view.range(), view.len()), (2, Some((6, 7)), 2));
+		assert_eq!(view.min(), Some((6, &6)));
+		assert_eq!(view.items().map(|&(k, _)| k).collect::<Vec<u32>>(), vec![6, 7]);
+		assert!(heap.bucket_view(33).is_none());
+
+		let hot: Vec<usize> = heap.bucket_views().filter(|v| !v.is_empty()).map(|v| v.index()).collect();
+		assert_eq!(hot, vec![1, 2, 8]);
+		assert_eq!(heap.bucket_views().filter(|v| v.range().is_none()).count(), 1);
+	}
+
+	#[test]
+	fn test_peek_bucket_tops() {
+		let mut heap = RadixHeap::new();
+		assert_eq!(heap.peek_bucket_tops().count(), 0);
+
+		for key in [4, 9, 6, 5, 7, 200, 130] { heap.push(key, key * 10).unwrap(); }
+		heap.pop();
+
+		let tops: Vec<(usize, u32, &u32)> = heap.peek_bucket_tops().collect();
+		assert_eq!(tops, vec![(1, 5, &50), (2, 6, &60), (4, 9, &90), (8, 130, &1300)]);
+		assert_eq!(heap.len(), 6);
+	}
+
+	#[test]
+	fn test_n_smallest() {
+		let mut rng = rand::thread_rng();
+		let mut heap = RadixHeap::new();
+		let mut keys: Vec<u32> = (0..1000).map(|_| rng.gen_range(0, 1 << 16)).collect();
+		for &key in &keys { heap.push(key, key).unwrap(); }
+		keys.sort_unstable();
+
+		for n in [0, 1, 7, 500, 1000, 2000] {
+			let smallest = heap.n_smallest(n);
+			assert!(smallest.iter().all(|&(k, v)| k == *v));
+			assert_eq!(smallest.iter().map(|&(k, _)| k).collect::<Vec<u32>>(), keys[..n.min(1000)]);
+		}
+
+		let first = heap.pop().unwrap().0;
+		heap.push(first, 0).unwrap();
+		assert_eq!(heap.n_smallest(1), vec![(first, &0)]);
+		assert_eq!(heap.len(), 1000);
+	}
+
+	#[test]
+	fn test_kth_smallest_key() {
+		let mut rng = rand::thread_rng();
+		let mut heap = RadixHeap::new();
+		let mut keys: Vec<u32> = (0..1000).map(|_| rng.gen()).collect();
+		for &key in &keys { heap.push(key, ()).unwrap(); }
+
+		heap.pop();
+		heap.push(heap.bound(), ()).unwrap();
+		keys.sort_unstable();
+
+		for k in [0, 1, 2, 499, 950, 999] { assert_eq!(heap.kth_smallest_key(k), Some(keys[k])); }
+		assert_eq!(heap.kth_smallest_key(1000), None);
+		assert_eq!(RadixHeap::<()>::new().kth_smallest_key(0), None);
+	}
+
+	#[test]
+	fn test_peek_key_value() {
+		let mut heap = RadixHeap::new();
+		assert_eq!((heap.peek_key(), heap.peek_value()), (None, None));
+
+		for (key, val) in [(9, "nine"), (4, "four"), (6, "six")] { heap.push(key, val.to_string()).unwrap(); }
+		assert_eq!((heap.peek_key(), heap.peek_value().map(String::as_str)), (Some(4), Some("four")));
+
+		heap.pop();
+		assert_eq!(heap.peek_key(), Some(6));
+		assert_eq!(heap.peek_value(), heap.peek().map(|(_, v)| v).as_ref());
+	}
+
+	#[test]
+	fn test_process_until() {
+		let mut heap = RadixHeap::new();
+		for key in 0..100 { heap.push(key, key).unwrap(); }
+
+		let mut seen = Vec::new();
+		assert_eq!(heap.process_until(4, Duration::from_secs(60), |k, _| seen.push(k)), 5);
+		assert_eq!(seen, vec![0, 1, 2, 3, 4]);
+		assert_eq!(heap.pop_for(Duration::ZERO, |_, _| {}), 0);
+
+		// the budget is checked between items
+		let slow = |_, _| std::thread::sleep(Duration::from_millis(5));
+		let count = heap.pop_for(Duration::from_millis(20), slow);
+		assert!((1..95).contains(&count));
+		assert_eq!(heap.len(), 95 - count);
+	}
+
+	#[test]
+	fn test_pop_while() {
+		let mut heap = RadixHeap::new();
+		for (key, val) in [(3, "c"), (1, "a"), (8, "x"), (2, "b"), (5, "stop")] { heap.push(key, val).unwrap(); }
+
+		let due: Vec<(u32, &str)> = heap.pop_while(|k, _| k <= 3).collect();
+		assert_eq!(due, vec![(1, "a"), (2, "b"), (3, "c")]);
+
+		// the rejected item is left in place
+		assert_eq!(heap.pop_while(|_, v| *v != "stop").count(), 0);
+		assert_eq!((heap.peek_key(), heap.len()), (Some(5), 2));
+		assert_eq!(heap.pop_while(|_, _| true).count(), 2);
+	}
+
+	#[test]
+	fn test_push_after() {
+		let mut heap = RadixHeap::new();
+		assert_eq!(heap.push_after(5, 'a'), Ok(5));
+		heap.push(20, 'b').unwrap();
+		heap.pop();
+
+		assert_eq!(heap.push_after(0, 'c'), Ok(5));
+		assert_eq!(heap.push_after(10, 'd'), Ok(15));
+		assert_eq!(heap.push_after(u32::MAX, 'e'), Err("key overflow"));
+		assert_eq!(heap.len(), 3);
+	}
+
+	#[test]
+	fn test_hash_eq() {
+		use std::collections::HashSet;
+
+		let mut a = RadixHeap::new();
+		for (key, val) in [(9, 'x'), (3, 'y'), (9, 'z'), (40, 'x')] { a.push(key, val).unwrap(); }
+		let mut b = RadixHeap::builder().fifo_ties(true).build();
+		for (key, val) in [(40, 'x'), (9, 'z'), (9, 'x'), (3, 'y')] { b.push(key, val).unwrap(); }
+
+		let mut seen = HashSet::new();
+		assert!(seen.insert(a.clone()) && !seen.insert(b.clone()));
+		assert!(a == b);
+
+		// same items, but a different bound
+		a.pop();
+		b.pop();
+		b.push(3, 'y').unwrap();
+		a.push(3, 'y').unwrap();
+		assert!(a == b);
+		let mut c = RadixHeap::new();
+		for (key, val) in [(3, 'y'), (9, 'x'), (9, 'z'), (40, 'x')] { c.push(key, val).unwrap(); }
+		assert!(c != a && seen.insert(a));
+	}
+
+	#[test]
+	fn test_pop_group() {
+		let mut heap = RadixHeap::builder().fifo_ties(true).build();
+		for (key, val) in [(4, 'a'), (2, 'b'), (4, 'c'), (9, 'd'), (2, 'e'), (4, 'f')] { heap.push(key, val).unwrap(); }
+
+		assert_eq!(heap.pop_group(), Some((2, vec!['b', 'e'])));
+		assert_eq!(heap.pop_group(), Some((4, vec!['a', 'c', 'f'])));
+		assert_eq!(heap.pop_group(), Some((9, vec!['d'])));
+		assert_eq!(heap.pop_group(), None);
+	}
+
+	#[test]
+	fn test_cursor() {
+		let mut heap = RadixHeap::new();
+		for key in [2, 9, 5] { heap.push(key, key).unwrap(); }
+
+		let mut cursor = heap.cursor();
+		let first = cursor.next(&heap).unwrap().unwrap().0;
+
+		// appending pushes are picked up where they land ahead of the cursor
+		heap.push(40, 40).unwrap();
+		let mut rest = vec![first];
+		while let Some((key, _)) = cursor.next(&heap).unwrap() { rest.push(key); }
+		rest.sort_unstable();
+		assert_eq!(rest, vec![2, 5, 9, 40]);
+
+		// a push into a bucket the cursor has passed would be missed
+		let mut cursor = heap.cursor();
+		while cursor.next(&heap).unwrap().is_some_and(|(k, _)| k < 9) {}
+		heap.push(3, 3).unwrap();
+		assert!(cursor.is_stale(&heap));
+
+		let mut cursor = heap.cursor();
+		cursor.next(&heap).unwrap();
+		heap.pop();
+		assert!(cursor.is_stale(&heap));
+		assert_eq!(cursor.next(&heap), Err("heap restructured since cursor creation"));
+		assert!(cursor.next_or_restart(&heap).is_some());
+		assert!(!cursor.is_stale(&heap));
+	}
+
+	#[test]
+	fn test_push_or_replace() {
+		let mut heap = RadixHeap::new();
+		assert_eq!(heap.push_or_replace(50, 'a'), Ok(true));
+		assert_eq!(heap.push_or_replace(70, 'a'), Ok(false));
+		assert_eq!(heap.push_or_replace(30, 'a'), Ok(true));
+		assert_eq!(heap.push_or_replace(40, 'b'), Ok(true));
+		assert_eq!(heap.len(), 2);
+		assert!(heap.validate().is_ok());
+
+		assert_eq!(heap.pop(), Some((30, 'a')));
+		assert_eq!(heap.push_or_replace(10, 'b'), Err("key too small"));
+		assert_eq!(heap.pop(), Some((40, 'b')));
+		assert_eq!(heap.pop(), None);
+	}
+
+	#[test]
+	fn test_soft() {
+		let mut exact = RadixHeap::builder().stats(true).build();
+		let mut soft = RadixHeap::builder().soft(4).stats(true).build();
+		let mut left: Vec<u32> = (0..1000u32).map(|i| i.wrapping_mul(7919) % 1000).collect();
+		for key in &left { exact.push(*key, ()).unwrap(); }
+		for key in &left { soft.push(*key, ()).unwrap(); }
+		left.sort_unstable();
+
+		// every key popped is within 7 of the smallest key left
+		while let Some((key, _)) = soft.pop() {
+			assert!(key - left[0] < 8);
+			left.remove(left.binary_search(&key).unwrap());
+			assert!(soft.validate().is_ok());
+		}
+
+		assert!(left.is_empty());
+		while exact.pop().is_some() {}
+		assert!(soft.stats().unwrap().moved < exact.stats().unwrap().moved);
+	}
+
+	#[test]
+	fn test_peek_pop_max() {
+		let mut heap = RadixHeap::new();
+		assert_eq!(heap.pop_max(), None);
+		for (key, val) in [(8, 'a'), (300, 'b'), (20, 'c'), (300, 'd'), (8, 'e')] { heap.push(key, val).unwrap(); }
+
+		assert_eq!(heap.peek_max(), Some((300, 'd')));
+		assert_eq!(heap.pop_max(), Some((300, 'd')));
+		assert_eq!(heap.pop_max(), Some((300, 'b')));
+		assert_eq!(heap.pop(), Some((8, 'a')));
+		assert_eq!(heap.pop_max(), Some((20, 'c')));
+		assert_eq!(heap.pop_max(), Some((8, 'e')));
+		assert!(heap.is_empty() && heap.validate().is_ok());
+		assert_eq!(heap.bound(), 8);
+	}
+
+	#[test]
+	fn test_pop_range() {
+		let mut heap = RadixHeap::new();
+		for key in [3, 10, 11, 12, 13, 14, 15, 40, 41, 90] { heap.push(key, key * 2).unwrap(); }
+		heap.pop();
+
+		let window = heap.pop_range(12..=41);
+		assert_eq!(window.iter().map(|&(k, _)| k).collect::<Vec<u32>>(), vec![12, 13, 14, 15, 40, 41]);
+		assert_eq!(window[0], (12, 24));
+		assert!(heap.pop_range(..3).is_empty());
+		assert!(heap.validate().is_ok());
+
+		assert_eq!(heap.len(), 3);
+		assert_eq!(heap.keys(), vec![10, 11, 90]);
+		assert_eq!(heap.pop_range(..).len(), 3);
+		assert!(heap.is_empty());
+	}
+
+	#[test]
+	fn test_meld() {
+		let heap = |keys: &[u32], bound: u32| {
+			let mut heap = RadixHeap::new();
+			heap.push(bound, 0).unwrap();
+			heap.pop();
+			for key in keys { heap.push(*key, *key).unwrap(); }
+			heap
+		};
+
+		let (mut a, mut b) = (heap(&[30, 80], 20), heap(&[12, 25, 50], 10));
+		assert_eq!(a.meld(&mut b, MeldPolicy::Reject), Err("items below the higher bound"));
+		assert_eq!(a.meld(&mut b, MeldPolicy::Rebase), Err("heap with the higher bound is not empty"));
+		assert_eq!((a.len(), b.len()), (2, 3));
+
+		assert_eq!(a.meld(&mut b, MeldPolicy::Park), Ok(vec![(12, 12)]));
+		assert!(b.is_empty() && a.validate().is_ok());
+		assert_eq!((a.bound(), a.sorted_tuples().len()), (20, 4));
+
+		let (mut a, mut b) = (heap(&[], 20), heap(&[12, 25], 10));
+		assert_eq!(a.meld(&mut b, MeldPolicy::Rebase), Ok(vec![]));
+		assert_eq!(a.bound(), 10);
+		assert_eq!(a.pop(), Some((12, 12)));
+
+		// raising the bound of a heap redistributes its own items as well
+		let (mut a, mut b) = (heap(&[11, 40], 10), heap(&[35], 30));
+		assert_eq!(a.meld(&mut b, MeldPolicy::Park), Ok(vec![(11, 11)]));
+		assert!(a.validate().is_ok());
+		assert_eq!(a.keys().len(), 2);
+	}
+
+	#[test]
+	fn test_shift_keys() {
+		let mut heap = RadixHeap::new();
+		for key in [4, 5, 7, 9, 600] { heap.push(key, key).unwrap(); }
+		heap.pop();
+
+		// keeps the layout
+		heap.shift_keys(1024).unwrap();
+		assert_eq!(heap.bound(), 1028);
+		assert!(heap.validate().is_ok());
+
+		// needs redistribution
+		heap.shift_keys(-1000).unwrap();
+		assert!(heap.validate().is_ok());
+		assert_eq!(heap.sorted_tuples(), vec![(29, 5), (31, 7), (33, 9), (624, 600)]);
+
+		assert_eq!(heap.shift_keys(-28), Ok(()));
+		assert_eq!(heap.shift_keys(-1), Err("key overflow"));
+		assert_eq!(heap.shift_keys(i64::from(u32::MAX)), Err("key overflow"));
+		assert_eq!(heap.pop(), Some((1, 5)));
+	}
+
+	#[test]
+	fn test_key_domain() {
+		let edges = [0, 1, 2, 3, (1 << 31) - 1, 1 << 31, (1 << 31) + 1, u32::MAX - 2, u32::MAX - 1, u32::MAX];
+
+		for &bound in &edges {
+			let mut heap = RadixHeap::new();
+			heap.push(bound, 0).unwrap();
+			heap.pop();
+
+			let mut keys: Vec<u32> = edges.iter().copied().filter(|&k| k >= bound).collect();
+			for &key in keys.iter().chain(keys.iter().rev()) { heap.push(key, key).unwrap(); }
+			for &key in &keys {
+				let (low, high) = heap.bucket_view(heap.bucket_for_key(key).unwrap()).unwrap().range().unwrap();
+				assert!(low <= key && key <= high);
+			}
+
+			assert!(heap.validate().is_ok());
+			if bound > 0 { assert_eq!(heap.push(bound - 1, 0), Err("key too small")); }
+
+			let mut popped = Vec::new();
+			while let Some((key, val)) = heap.pop() {
+				assert_eq!(key, val);
+				assert!(heap.validate().is_ok());
+				popped.push(key);
+			}
+
+			keys.extend(keys.clone());
+			keys.sort_unstable();
+			assert_eq!(popped, keys);
+
+			// the largest key can be pushed again once it has been popped
+			assert_eq!(heap.bound(), u32::MAX);
+			assert_eq!(heap.push(u32::MAX, 1), Ok(()));
+			assert_eq!(heap.pop(), Some((u32::MAX, 1)));
+		}
+	}
+
+	#[test]
+	fn test_iteration_order() {
+		let mut heap = RadixHeap::new();
+		for (key, val) in [(9, 'a'), (2, 'b'), (9, 'c'), (300, 'd'), (2, 'e'), (310, 'f')] { heap.push(key, val).unwrap(); }
+		let before = heap.tuples();
+		assert_eq!(before, vec![(2, 'b'), (2, 'e'), (9, 'a'), (9, 'c'), (300, 'd'), (310, 'f')]);
+		assert_eq!(heap.sorted_tuples(), before);
+
+		// only the items of the restructured bucket move
+		heap.pop();
+		assert_eq!(heap.tuples(), vec![(2, 'e'), (9, 'a'), (9, 'c'), (300, 'd'), (310, 'f')]);
+		assert_eq!(heap.clone().tuples(), heap.tuples());
+
+		heap.push(9, 'g').unwrap();
+		assert_eq!(heap.sorted_tuples().iter().map(|&(_, v)| v).collect::<String>(), "eacgdf");
+	}
+
+	#[test]
+	// Miri aborts on the deliberately failing huge allocation
+	#[cfg_attr(miri, ignore)]
+	fn test_try_reserve() {
+		let mut heap = RadixHeap::new();
+		assert_eq!(heap.try_reserve(usize::MAX), Err("allocation failed"));
+		assert_eq!(heap.try_reserve(330), Ok(()));
+		assert!(heap.capacity() >= 330);
+
+		assert_eq!(heap.try_extend((0..100).map(|k| (k, k))), Ok(()));
+		heap.pop();
+		heap.pop();
+		assert_eq!(heap.try_extend(vec![(5, 5), (0, 0), (6, 6)]), Err("key too small"));
+		assert_eq!(heap.len(), 99);
+		assert_eq!(heap.try_push(1, 1), Ok(()));
+	}
+
+	#[test]
+	fn test_key_histogram() {
+		let mut heap = RadixHeap::new();
+		for key in &[3, 12, 17, 18, 19, 40, 1000, 1003] { heap.push(*key, ()).unwrap(); }
+		heap.pop();
+
+		assert_eq!(heap.key_histogram(10), BTreeMap::from([(10, 4), (40, 1), (1000, 2)]));
+		assert_eq!(heap.key_histogram(1 << 31), BTreeMap::from([(0, 7)]));
+		assert_eq!(heap.key_histogram(1).values().sum::<usize>(), heap.len());
+	}
+
+	#[test]
+	fn test_clone_from() {
+		let mut source = RadixHeap::new();
+		let mut target = RadixHeap::with_capacity(33 * 100);
+		let buffers: Vec<*const (u32, u64)> = target.buckets.iter().map(|b| b.items.as_ptr()).collect();
+
+		for step in 0..50u32 {
+			source.push(step * 7, u64::from(step)).unwrap();
+			if step % 3 == 0 { source.pop(); }
+
+			target.clone_from(&source);
+			assert_eq!(target.tuples(), source.tuples());
+			assert_eq!(target.bound(), source.bound());
+			assert_eq!(target.validate(), Ok(()));
+		}
+
+		assert_eq!(target.buckets.iter().map(|b| b.items.as_ptr()).collect::<Vec<_>>(), buffers);
+	}
+
+	#[test]
+	fn test_capacity() {
+		let heap: RadixHeap<&str> = RadixHeap::with_capacity(396usize);
+		assert_eq!(heap.capacity(), 396usize);
+		assert!(RadixHeap::<u8>::with_capacity(40).capacity() >= 40);
+
+		#[allow(deprecated)]
+		let heap: RadixHeap<&str> = RadixHeap::with_bucket_capacity(Some(12usize));
+		assert_eq!(heap.capacity(), 396usize);
+
+		let mut heap = RadixHeap::new();
+		for key in 0..1000 { heap.push(key, ()).unwrap(); }
+		let capacity = heap.capacity();
+
+		heap.clear();
+		assert_eq!(heap.capacity(), capacity);
+		heap.clear_shrink();
+		assert_eq!(heap.capacity(), 0);
+		assert_eq!(heap.len(), 0usize);
+		assert!(heap.is_empty());
+	}
+
+	#[test]
+	#[allow(unused_must_use)]
+	fn test_tuples() {
+		let mut heap: RadixHeap<&str> = RadixHeap::with_capacity(1584usize);
+		// let mut tupkeys: Vec<u32> = Vec::with_capacity(10usize);
+
+		heap.push(289371, "library");
+		heap.push(259, "radix");
+		heap.push(98612, "heap");
+		heap.push(34, "rust");
+
+		assert_eq!(heap.tuples()
+		               .into_iter()
+			           .map(|(k, _)| k)
+			           .collect::<Vec<u32>>(),
+		           vec![34, 259, 98612, 289371]);
+		assert_eq!(heap.sorted_tuples(), heap.tuples());
+		assert_eq!(heap.values(), vec!["rust", "radix", "heap", "library"]);
+		assert_eq!(heap.len(), 4usize);
+		assert_eq!(heap.capacity(), 1584usize);
+		assert!(!heap.is_empty());
+
+		heap.clear();
+		assert!(heap.is_empty());
+		assert_eq!(heap.len(), 0usize);
+
+		heap.push(15, "seven");
+		heap.push(9, "four");
+		heap.push(13, "thirteen");
+		heap.push(12, "twelve");
+		heap.push(10, "ten");
+		heap.push(11, "eleven");
+		heap.push(8, "eight");
+		heap.push(17, "seventeen");
+		heap.push(3, "three");
+
+		assert_ne!(heap.tuples(), heap.sorted_tuples());
+		assert_eq!(heap.tuples()
+			           .into_iter()
+			           .map(|(k, _)| k)
+			           .collect::<Vec<u32>>(),
+		           vec![3u32, 15, 9, 13, 12, 10, 11, 8, 17]);
+		assert_eq!(heap.sorted_tuples()
+					   .into_iter()
+					   .map(|(k, _)| k)
+			           .collect::<Vec<u32>>(),
+		           vec![3u32, 8, 9, 10, 11, 12, 13, 15, 17]);
+	}
+}
@@ -0,0 +1,107 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: huffman.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use crate::RadixHeap;
+
+// code length of every symbol (indexed like "frequencies"); symbols with
+// a frequency of zero get no code and a length of zero
+pub fn code_lengths(frequencies: &[u32]) -> Result<Vec<u32>, &'static str> {
+	let mut heap: RadixHeap<usize> = RadixHeap::new();
+	let mut parent: Vec<usize> = vec![usize::MAX; frequencies.len()];
+
+	for (symbol, &frequency) in frequencies.iter().enumerate() {
+		if frequency > 0 { heap.push(frequency, symbol).unwrap(); }
+	}
+
+	// a single symbol still needs one bit
+	if heap.len() == 1 {
+		return Ok(frequencies.iter().map(|&f| if f > 0 { 1 } else { 0 }).collect());
+	}
+
+	while heap.len() > 1 {
+		let (wa, a) = heap.pop().unwrap();
+		let (wb, b) = heap.pop().unwrap();
+		let node = parent.len();
+
+		parent.push(usize::MAX);
+		parent[a] = node;
+		parent[b] = node;
+
+		// merged weights never decrease, so the heap stays monotone
+		let weight = wa.checked_add(wb).ok_or("frequency sum too large")?;
+		heap.push(weight, node).unwrap();
+	}
+
+	Ok((0..frequencies.len()).map(|symbol| {
+		let mut length = 0;
+		let mut node = symbol;
+
+		while parent[node] != usize::MAX {
+			node = parent[node];
+			length += 1;
+		}
+
+		length
+	}).collect())
+}
+
+// canonical prefix code "(code, length)" of every symbol, the code being
+// stored in the lowest "length" bits; unused symbols are mapped to "None"
+pub fn codes(frequencies: &[u32]) -> Result<Vec<Option<(u64, u32)>>, &'static str> {
+	let lengths = code_lengths(frequencies)?;
+	let mut symbols: Vec<usize> = (0..lengths.len()).filter(|&s| lengths[s] > 0).collect();
+	let mut codes: Vec<Option<(u64, u32)>> = vec![None; lengths.len()];
+	let mut code: u64 = 0;
+	let mut previous: u32 = 0;
+
+	symbols.sort_by_key(|&s| (lengths[s], s));
+
+	for (i, &symbol) in symbols.iter().enumerate() {
+		if i > 0 { code += 1; }
+		code <<= lengths[symbol] - previous;
+		previous = lengths[symbol];
+		codes[symbol] = Some((code, previous));
+	}
+
+	Ok(codes)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_code_lengths() {
+		assert_eq!(code_lengths(&[5, 9, 12, 13, 16, 45]), Ok(vec![4, 4, 3, 3, 3, 1]));
+		assert_eq!(code_lengths(&[0, 7, 0]), Ok(vec![0, 1, 0]));
+		assert_eq!(code_lengths(&[]), Ok(vec![]));
+		assert!(code_lengths(&[u32::MAX, u32::MAX]).is_err());
+	}
+
+	#[test]
+	fn test_codes() {
+		assert_eq!(codes(&[1, 1, 2, 0]),
+		           Ok(vec![Some((0b10, 2)), Some((0b11, 2)), Some((0b0, 1)), None]));
+	}
+}
@@ -0,0 +1,180 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: hybrid.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use crate::RadixHeap;
+
+// values up to this size are stored inline, larger ones behind a pointer
+pub const INLINE_LIMIT: usize = 32;
+
+// how the values of a type are stored by a "HybridRadixHeap"; implemented
+// through "hybrid_storage!", which decides by the size of the type
+pub trait Storage: Debug + Ord + Sized {
+	type Slot: Clone + Debug + Ord;
+	fn wrap(self) -> Self::Slot;
+	fn unwrap(slot: Self::Slot) -> Self;
+	fn get(slot: &Self::Slot) -> &Self;
+}
+
+pub struct Choose<const BOXED: bool>;
+
+pub trait Layout<V> {
+	type Slot: Clone + Debug + Ord;
+	fn wrap(val: V) -> Self::Slot;
+	fn unwrap(slot: Self::Slot) -> V;
+	fn get(slot: &Self::Slot) -> &V;
+}
+
+impl<V: Clone + Debug + Ord> Layout<V> for Choose<false> {
+	type Slot = V;
+	fn wrap(val: V) -> V { val }
+	fn unwrap(slot: V) -> V { slot }
+	fn get(slot: &V) -> &V { slot }
+}
+
+// see "BoxedRadixHeap" on why popped pointers are never shared; clones of
+// a "HybridRadixHeap" copy boxed values likewise
+impl<V: Debug + Ord> Layout<V> for Choose<true> {
+	type Slot = Arc<V>;
+	fn wrap(val: V) -> Arc<V> { Arc::new(val) }
+	fn unwrap(slot: Arc<V>) -> V { Arc::try_unwrap(slot).expect("popped value still shared") }
+	fn get(slot: &Arc<V>) -> &V { slot }
+}
+
+// implements "Storage" for the given types, storing those larger than
+// "INLINE_LIMIT" bytes behind a pointer; inline types must be "Clone"
+#[macro_export]
+macro_rules! hybrid_storage {
+	($($t:ty),*) => {
+		$(
+			impl $crate::hybrid::Storage for $t {
+				type Slot = <$crate::hybrid::Choose<{ std::mem::size_of::<$t>() > $crate::hybrid::INLINE_LIMIT }>
+					as $crate::hybrid::Layout<$t>>::Slot;
+
+				fn wrap(self) -> Self::Slot {
+					<$crate::hybrid::Choose<{ std::mem::size_of::<$t>() > $crate::hybrid::INLINE_LIMIT }>
+						as $crate::hybrid::Layout<$t>>::wrap(self)
+				}
+
+				fn unwrap(slot: Self::Slot) -> $t {
+					<$crate::hybrid::Choose<{ std::mem::size_of::<$t>() > $crate::hybrid::INLINE_LIMIT }>
+						as $crate::hybrid::Layout<$t>>::unwrap(slot)
+				}
+
+				fn get(slot: &Self::Slot) -> &$t {
+					<$crate::hybrid::Choose<{ std::mem::size_of::<$t>() > $crate::hybrid::INLINE_LIMIT }>
+						as $crate::hybrid::Layout<$t>>::get(slot)
+				}
+			}
+		)*
+	}
+}
+
+hybrid_storage!((), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, String);
+
+// radix heap keeping small values inline for cache locality and large
+// ones boxed so restructuring stays cheap
+#[derive(Debug)]
+pub struct HybridRadixHeap<V: Storage> {
+	heap: RadixHeap<V::Slot>
+}
+
+impl<V: Storage> HybridRadixHeap<V> {
+	pub fn new() -> HybridRadixHeap<V> { HybridRadixHeap { heap: RadixHeap::new() } }
+
+	pub fn len(&self) -> usize { self.heap.len() }
+
+	pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+	pub fn bound(&self) -> u32 { self.heap.bound() }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { self.heap.push(key, val.wrap()) }
+
+	pub fn pop(&mut self) -> Option<(u32, V)> { self.heap.pop().map(|(k, slot)| (k, V::unwrap(slot))) }
+
+	pub fn peek(&self) -> Option<(u32, &V)> { Some((self.heap.peek_key()?, V::get(self.heap.peek_value()?))) }
+
+	pub fn clear(&mut self) { self.heap.clear() }
+}
+
+// copies boxed values instead of sharing them with the clone
+impl<V: Storage + Clone> Clone for HybridRadixHeap<V> {
+	fn clone(&self) -> HybridRadixHeap<V> {
+		let mut heap = self.heap.clone();
+		for slot in heap.values_mut() { *slot = V::get(slot).clone().wrap(); }
+		HybridRadixHeap { heap }
+	}
+}
+
+impl<V: Storage> Default for HybridRadixHeap<V> {
+	fn default() -> HybridRadixHeap<V> { HybridRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::mem::size_of;
+
+	#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+	struct Small(u32, u32);
+
+	#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+	struct Large([u64; 32]);
+
+	#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+	struct Shared([u64; 8]);
+
+	hybrid_storage!(Small, Large, Shared);
+
+	#[test]
+	fn test_hybrid_storage() {
+		assert_eq!(size_of::<<Small as Storage>::Slot>(), size_of::<Small>());
+		assert_eq!(size_of::<<Large as Storage>::Slot>(), size_of::<usize>());
+
+		let mut small = HybridRadixHeap::new();
+		let mut large = HybridRadixHeap::new();
+
+		for key in [30, 10, 20] {
+			small.push(key, Small(key, 0)).unwrap();
+			large.push(key, Large([u64::from(key); 32])).unwrap();
+		}
+
+		assert_eq!(small.peek(), Some((10, &Small(10, 0))));
+		assert_eq!(large.pop().map(|(k, v)| (k, v.0[31])), Some((10, 10)));
+		assert_eq!(small.pop(), Some((10, Small(10, 0))));
+		assert_eq!(large.len() + small.len(), 4);
+	}
+
+	#[test]
+	fn test_clone() {
+		let mut heap = HybridRadixHeap::new();
+		for key in [3, 1, 2] { heap.push(key, Shared([u64::from(key); 8])).unwrap(); }
+
+		let mut copy = heap.clone();
+		assert_eq!(heap.pop(), Some((1, Shared([1; 8]))));
+		assert_eq!(copy.pop(), Some((1, Shared([1; 8]))));
+		assert_eq!(copy.len() + heap.len(), 4);
+	}
+}
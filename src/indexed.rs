@@ -0,0 +1,166 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: indexed.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use crate::RadixHeap;
+
+// radix heap holding every value at most once, with an index from values
+// to their keys; removed or replaced entries stay in the heap until they
+// come up, and are skipped then
+#[derive(Clone, Debug)]
+pub struct IndexedRadixHeap<V: Clone + Debug + Ord + Hash> {
+	// every heap entry carries a ticket, entries whose ticket no longer
+	// matches the one in the index are stale
+	heap: RadixHeap<(V, u64)>,
+	index: HashMap<V, (u32, u64)>,
+	next: u64
+}
+
+impl<V: Clone + Debug + Ord + Hash> IndexedRadixHeap<V> {
+	pub fn new() -> IndexedRadixHeap<V> {
+		IndexedRadixHeap { heap: RadixHeap::new(), index: HashMap::new(), next: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.index.len() }
+
+	pub fn is_empty(&self) -> bool { self.index.is_empty() }
+
+	pub fn bound(&self) -> u32 { self.heap.bound() }
+
+	pub fn contains_value(&self, val: &V) -> bool { self.index.contains_key(val) }
+
+	pub fn get_key_of(&self, val: &V) -> Option<u32> { self.index.get(val).map(|&(k, _)| k) }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if self.index.contains_key(&val) { return Err("value already stored"); }
+		self.insert(key, val)
+	}
+
+	// same as "RadixHeap::push_or_replace()", but without searching
+	pub fn push_or_replace(&mut self, key: u32, val: V) -> Result<bool, &'static str> {
+		if key < self.heap.bound() { return Err("key too small"); }
+		if self.get_key_of(&val).is_some_and(|k| k <= key) { return Ok(false); }
+
+		self.insert(key, val)?;
+		self.compact();
+		Ok(true)
+	}
+
+	// returns the key the value was stored under
+	pub fn remove(&mut self, val: &V) -> Option<u32> {
+		let (key, _) = self.index.remove(val)?;
+		self.compact();
+		Some(key)
+	}
+
+	pub fn peek(&mut self) -> Option<(u32, &V)> {
+		self.skip_stale();
+		self.heap.peek_key().zip(self.heap.peek_value().map(|(v, _)| v))
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		self.skip_stale();
+
+		let (key, (val, _)) = self.heap.pop()?;
+		self.index.remove(&val);
+		Some((key, val))
+	}
+
+	pub fn clear(&mut self) {
+		self.heap.clear();
+		self.index.clear();
+	}
+
+	fn insert(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		self.heap.push(key, (val.clone(), self.next))?;
+		self.index.insert(val, (key, self.next));
+		self.next += 1;
+		Ok(())
+	}
+
+	fn skip_stale(&mut self) {
+		while let Some((val, ticket)) = self.heap.peek_value() {
+			if self.index.get(val).is_some_and(|&(_, t)| t == *ticket) { break; }
+			self.heap.pop();
+		}
+	}
+
+	// drops all stale entries once they outnumber the live ones; "clear()"
+	// keeps the bound of the heap
+	fn compact(&mut self) {
+		if self.heap.len() <= 2 * self.index.len() + 32 { return; }
+
+		self.heap.clear();
+		for (val, &(key, ticket)) in &self.index { self.heap.push(key, (val.clone(), ticket)).unwrap(); }
+	}
+}
+
+impl<V: Clone + Debug + Ord + Hash> Default for IndexedRadixHeap<V> {
+	fn default() -> IndexedRadixHeap<V> { IndexedRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_indexed() {
+		let mut heap = IndexedRadixHeap::new();
+		heap.push(40, "d").unwrap();
+		heap.push(20, "b").unwrap();
+		assert_eq!(heap.push(10, "b"), Err("value already stored"));
+
+		assert_eq!(heap.push_or_replace(10, "d"), Ok(true));
+		assert_eq!(heap.push_or_replace(30, "b"), Ok(false));
+		assert!(heap.contains_value(&"d"));
+		assert_eq!(heap.get_key_of(&"d"), Some(10));
+
+		heap.push(15, "c").unwrap();
+		assert_eq!(heap.remove(&"c"), Some(15));
+		assert_eq!(heap.len(), 2);
+
+		assert_eq!(heap.peek(), Some((10, &"d")));
+		assert_eq!(heap.pop(), Some((10, "d")));
+		assert_eq!(heap.pop(), Some((20, "b")));
+		assert_eq!(heap.pop(), None);
+		assert!(heap.is_empty());
+	}
+
+	#[test]
+	fn test_indexed_compact() {
+		let mut heap = IndexedRadixHeap::new();
+		for round in 0..100u32 {
+			for val in 0..10u32 { heap.push_or_replace(1000 - round, val).unwrap(); }
+		}
+
+		assert_eq!(heap.len(), 10);
+		assert!(heap.heap.len() <= 2 * 10 + 32 + 1);
+
+		let mut drained: Vec<(u32, u32)> = std::iter::from_fn(|| heap.pop()).collect();
+		drained.sort_unstable();
+		assert_eq!(drained, (0..10).map(|val| (901, val)).collect::<Vec<(u32, u32)>>());
+	}
+}
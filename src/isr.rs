@@ -0,0 +1,100 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: isr.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use core::cell::RefCell;
+use core::fmt::Debug;
+use critical_section::Mutex;
+use crate::static_heap::StaticRadixHeap;
+
+// timer queue shared between interrupt handlers and the main loop, e.g. an
+// ISR pushing deadlines that the main loop pops once they are due. Every
+// operation runs inside a critical section; pushes never allocate and touch
+// at most 33 items, and fail with "heap is full" beyond "N" items.
+//
+//     static DEADLINES: IsrRadixHeap<Task, 16> = IsrRadixHeap::new();
+pub struct IsrRadixHeap<V, const N: usize> {
+	heap: Mutex<RefCell<StaticRadixHeap<V, N>>>
+}
+
+impl<V: Clone + Debug + Ord, const N: usize> IsrRadixHeap<V, N> {
+	pub const fn new() -> IsrRadixHeap<V, N> { IsrRadixHeap { heap: Mutex::new(RefCell::new(StaticRadixHeap::new())) } }
+
+	// runs "f" inside one critical section, for several operations in one go
+	pub fn with<R, F: FnOnce(&mut StaticRadixHeap<V, N>) -> R>(&self, f: F) -> R {
+		critical_section::with(|cs| f(&mut self.heap.borrow_ref_mut(cs)))
+	}
+
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> { self.with(|heap| heap.push(key, val)) }
+
+	pub fn pop(&self) -> Option<(u32, V)> { self.with(|heap| heap.pop()) }
+
+	// pops the next item if its deadline is not after "now"
+	pub fn pop_due(&self, now: u32) -> Option<(u32, V)> {
+		self.with(|heap| if heap.peek()?.0 <= now { heap.pop() } else { None })
+	}
+
+	pub fn peek(&self) -> Option<(u32, V)> { self.with(|heap| heap.peek().map(|(k, v)| (k, v.clone()))) }
+
+	// next deadline, e.g. to program a hardware timer
+	pub fn next_key(&self) -> Option<u32> { self.with(|heap| heap.peek().map(|(k, _)| k)) }
+
+	pub fn len(&self) -> usize { self.with(|heap| heap.len()) }
+
+	pub fn is_empty(&self) -> bool { self.with(|heap| heap.is_empty()) }
+
+	pub fn bound(&self) -> u32 { self.with(|heap| heap.bound()) }
+
+	pub fn clear(&self) { self.with(|heap| heap.clear()) }
+}
+
+impl<V: Clone + Debug + Ord, const N: usize> Default for IsrRadixHeap<V, N> {
+	fn default() -> IsrRadixHeap<V, N> { IsrRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::thread;
+
+	static DEADLINES: IsrRadixHeap<u32, 64> = IsrRadixHeap::new();
+
+	#[test]
+	fn test_isr_heap() {
+		// the spawned thread plays the interrupt handler
+		let isr = thread::spawn(|| for i in 0..64u32 { DEADLINES.push(100 + (i * 37) % 64, i).unwrap(); });
+		isr.join().unwrap();
+
+		assert_eq!(DEADLINES.push(500, 0), Err("heap is full"));
+		assert_eq!(DEADLINES.next_key(), Some(100));
+
+		let mut due = Vec::new();
+		while let Some((key, _)) = DEADLINES.pop_due(131) { due.push(key); }
+		assert_eq!(due, (100..=131).collect::<Vec<u32>>());
+		assert_eq!(DEADLINES.push(120, 0), Err("key too small"));
+		assert_eq!(DEADLINES.len(), 32);
+
+		DEADLINES.clear();
+		assert!(DEADLINES.is_empty() && DEADLINES.pop().is_none());
+	}
+}
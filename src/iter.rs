@@ -0,0 +1,185 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: iter.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::iter::FusedIterator;
+use crate::bucket::Bucket;
+use crate::heap::RadixHeap;
+
+pub struct BucketIter<'a, V: 'a + Ord> {
+	pub(crate) container: &'a Bucket<V>,
+	pub(crate) index: usize
+}
+
+pub struct IntoBucketIter<V: Clone + Ord> {
+	pub(crate) container: Bucket<V>,
+	pub(crate) index: usize
+}
+
+// position within a heap that does not borrow it, so the heap may be
+// changed while iterating: pushes into the current or later buckets keep
+// the cursor valid, while pushes into buckets already passed, and anything
+// moving or removing items, make it stale
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor {
+	pub(crate) generation: u64,
+	pub(crate) bucket: usize,
+	pub(crate) item: usize,
+	// number of items in the buckets already passed when passing them
+	pub(crate) behind: usize
+}
+
+pub struct RadixBucketIter<'a, V: 'a + Debug + Ord> {
+	pub(crate) container: &'a RadixHeap<V>,
+	pub(crate) index: usize
+}
+
+pub struct IntoRadixBucketIter<V: Clone + Debug + Ord> {
+	pub(crate) container: RadixHeap<V>,
+	pub(crate) index: usize
+}
+
+// pops items while "predicate" holds for them; the first item it rejects
+// stays in the heap and ends the iteration
+pub struct PopWhile<'a, V: Clone + Debug + Ord, P: FnMut(u32, &V) -> bool> {
+	pub(crate) heap: &'a mut RadixHeap<V>,
+	pub(crate) predicate: P,
+	pub(crate) done: bool
+}
+
+impl<'a, V: 'a + Clone + Ord> Iterator for BucketIter<'a, V> {
+	type Item = &'a (u32, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.container.length() { None } else {
+			self.index += 1;
+			Some(&self.container.items[self.index - 1])
+		}
+	}
+}
+
+impl<V: Clone + Ord> Iterator for IntoBucketIter<V> {
+	type Item = (u32, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.container.length() { None } else {
+			self.index += 1;
+			Some(self.container.items[self.index - 1].clone())
+		}
+	}
+}
+
+impl<V: Clone + Ord> IntoIterator for Bucket<V> {
+	type Item = (u32, V);
+	type IntoIter = IntoBucketIter<V>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IntoBucketIter { container: self, index: 0 }
+	}
+}
+
+impl<'a, V: 'a + Clone + Debug + Ord> Iterator for RadixBucketIter<'a, V> {
+	type Item = &'a Bucket<V>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.container.buckets.len() { None } else {
+			self.index += 1;
+			Some(&self.container.buckets[self.index - 1])
+		}
+	}
+}
+
+impl<V: Clone + Debug + Ord> Iterator for IntoRadixBucketIter<V> {
+	type Item = Bucket<V>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.container.buckets.len() { None } else {
+			self.index += 1;
+			Some(self.container.buckets[self.index - 1].clone())
+		}
+	}
+}
+
+impl<'a, V: Clone + Debug + Ord, P: FnMut(u32, &V) -> bool> Iterator for PopWhile<'a, V, P> {
+	type Item = (u32, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done { return None; }
+
+		let key = self.heap.peek_key()?;
+		if !(self.predicate)(key, self.heap.peek_value()?) {
+			self.done = true;
+			return None;
+		}
+
+		self.heap.pop()
+	}
+}
+
+impl<'a, V: Clone + Debug + Ord, P: FnMut(u32, &V) -> bool> FusedIterator for PopWhile<'a, V, P> {}
+
+impl<V: Clone + Debug + Ord> IntoIterator for RadixHeap<V> {
+	type Item = Bucket<V>;
+	type IntoIter = IntoRadixBucketIter<V>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IntoRadixBucketIter { container: self, index: 0 }
+	}
+}
+
+impl Cursor {
+	// items only ever get removed along with a new generation, so a push
+	// behind the cursor shows in the lengths of the buckets passed
+	pub fn is_stale<V: Clone + Debug + Ord>(&self, heap: &RadixHeap<V>) -> bool {
+		self.generation != heap.generation
+			|| heap.buckets.iter().take(self.bucket).map(|b| b.length()).sum::<usize>() != self.behind
+	}
+
+	pub fn restart<V: Clone + Debug + Ord>(&mut self, heap: &RadixHeap<V>) { *self = heap.cursor(); }
+
+	// next item in bucket order; fails instead of yielding items from a
+	// layout the cursor was not created for
+	pub fn next<'a, V: Clone + Debug + Ord>(&mut self, heap: &'a RadixHeap<V>) -> Result<Option<(u32, &'a V)>, &'static str> {
+		if self.is_stale(heap) { return Err("heap restructured since cursor creation"); }
+
+		while let Some(bucket) = heap.buckets.get(self.bucket) {
+			if let Some((key, val)) = bucket.items.get(self.item) {
+				self.item += 1;
+				return Ok(Some((*key, val)));
+			}
+
+			self.behind += bucket.length();
+			self.bucket += 1;
+			self.item = 0;
+		}
+
+		Ok(None)
+	}
+
+	// like "next()", but starts over at the first item once stale
+	pub fn next_or_restart<'a, V: Clone + Debug + Ord>(&mut self, heap: &'a RadixHeap<V>) -> Option<(u32, &'a V)> {
+		if self.is_stale(heap) { self.restart(heap); }
+		self.next(heap).unwrap()
+	}
+}
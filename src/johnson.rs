@@ -0,0 +1,121 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: johnson.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::convert::TryFrom;
+use crate::dijkstra::dijkstra;
+
+// yields the distances from one source after the other, so that the n²
+// distances of all pairs never need to be held at once
+#[derive(Clone, Debug)]
+pub struct AllPairs {
+	reweighted: Vec<Vec<(usize, u32)>>,
+	potential: Vec<i64>,
+	next: usize
+}
+
+// all pairs shortest paths by Johnson's algorithm for graphs with negative
+// edge weights: Bellman-Ford computes potentials making every weight
+// non-negative, then Dijkstra runs once per source on the radix heap.
+// Fails on negative cycles and if a reweighted edge does not fit a "u32".
+pub fn johnson(graph: &[Vec<(usize, i32)>]) -> Result<AllPairs, &'static str> {
+	let potential = potentials(graph)?;
+
+	let reweighted = graph.iter().enumerate().map(|(u, edges)| {
+		edges.iter().map(|&(v, w)| {
+			let weight = i64::from(w) + potential[u] - potential[v];
+			u32::try_from(weight).map(|w| (v, w)).map_err(|_| "weight overflow")
+		}).collect()
+	}).collect::<Result<_, _>>()?;
+
+	Ok(AllPairs { reweighted, potential, next: 0 })
+}
+
+// Bellman-Ford from a virtual source with zero weight edges to all nodes
+fn potentials(graph: &[Vec<(usize, i32)>]) -> Result<Vec<i64>, &'static str> {
+	let mut potential = vec![0i64; graph.len()];
+
+	for _ in 0..=graph.len() {
+		let mut changed = false;
+
+		for (u, edges) in graph.iter().enumerate() {
+			for &(v, w) in edges {
+				let d = potential[u] + i64::from(w);
+				if d < potential[v] {
+					potential[v] = d;
+					changed = true;
+				}
+			}
+		}
+
+		if !changed { return Ok(potential); }
+	}
+
+	Err("negative cycle")
+}
+
+impl Iterator for AllPairs {
+	type Item = (usize, Vec<Option<i64>>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next >= self.reweighted.len() { return None; }
+
+		let source = self.next;
+		self.next += 1;
+
+		let dist = dijkstra(&self.reweighted, source).into_iter().enumerate()
+			.map(|(v, d)| d.map(|d| i64::from(d) - self.potential[source] + self.potential[v]))
+			.collect();
+		Some((source, dist))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let left = self.reweighted.len() - self.next;
+		(left, Some(left))
+	}
+}
+
+impl ExactSizeIterator for AllPairs {}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_johnson() {
+		let mut graph: Vec<Vec<(usize, i32)>> = vec![Vec::new(); 6];
+		for &(u, v, w) in &[(0, 1, 3), (0, 2, 8), (0, 4, -4), (1, 3, 1), (1, 4, 7),
+		                    (2, 1, 4), (3, 0, 2), (3, 2, -5), (4, 3, 6)] {
+			graph[u].push((v, w));
+		}
+
+		let rows: Vec<(usize, Vec<Option<i64>>)> = johnson(&graph).unwrap().collect();
+		assert_eq!(rows.len(), 6);
+		assert_eq!(rows[0].1, vec![Some(0), Some(1), Some(-3), Some(2), Some(-4), None]);
+		assert_eq!(rows[1].1, vec![Some(3), Some(0), Some(-4), Some(1), Some(-1), None]);
+		assert_eq!(rows[5].1, vec![None, None, None, None, None, Some(0)]);
+
+		graph[4].push((0, -5));
+		assert_eq!(johnson(&graph).unwrap_err(), "negative cycle");
+	}
+}
@@ -0,0 +1,187 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: key.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::time::Duration;
+
+// how a scaled value is turned into an integer key; every mode maps larger
+// values to keys at least as large, so the order of values survives
+// quantization except for values ending up with the same key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+	Floor,
+	Ceil,
+	// halfway cases round up
+	Nearest
+}
+
+impl Rounding {
+	pub fn apply(self, value: f64) -> f64 {
+		match self {
+			Rounding::Floor => value.floor(),
+			Rounding::Ceil => value.ceil(),
+			Rounding::Nearest => (value + 0.5).floor()
+		}
+	}
+}
+
+// conversion of fractional values, e.g. seconds, into integer keys at a
+// fixed resolution: a value "v" becomes the key "floor(v * per_unit)", or
+// is rounded otherwise as set by "rounded()". Values below zero or beyond
+// the key range are reported as errors by the checked conversions and
+// clamped by the saturating ones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyScale {
+	per_unit: f64,
+	rounding: Rounding
+}
+
+impl KeyScale {
+	// keys of a millisecond, microsecond, and nanosecond when values are
+	// given in seconds
+	pub const MILLIS: KeyScale = KeyScale { per_unit: 1e3, rounding: Rounding::Floor };
+	pub const MICROS: KeyScale = KeyScale { per_unit: 1e6, rounding: Rounding::Floor };
+	pub const NANOS: KeyScale = KeyScale { per_unit: 1e9, rounding: Rounding::Floor };
+
+	// "per_unit" keys make up one unit of the values
+	pub fn new(per_unit: f64) -> Result<KeyScale, &'static str> {
+		if !per_unit.is_finite() || per_unit <= 0.0 { return Err("invalid resolution"); }
+		Ok(KeyScale { per_unit, rounding: Rounding::Floor })
+	}
+
+	// one key stands for "resolution" units, e.g. 0.001 for milliseconds
+	pub fn with_resolution(resolution: f64) -> Result<KeyScale, &'static str> { KeyScale::new(1.0 / resolution) }
+
+	pub fn rounded(self, rounding: Rounding) -> KeyScale { KeyScale { rounding, ..self } }
+
+	pub fn per_unit(&self) -> f64 { self.per_unit }
+
+	pub fn rounding(&self) -> Rounding { self.rounding }
+
+	pub fn resolution(&self) -> f64 { 1.0 / self.per_unit }
+
+	fn scale(&self, value: f64, max: f64) -> Result<f64, &'static str> {
+		if value.is_nan() { return Err("value is not a number"); }
+
+		let scaled = self.rounding.apply(value * self.per_unit);
+		if scaled < 0.0 || scaled > max { Err("value out of range") } else { Ok(scaled) }
+	}
+
+	pub fn to_key(&self, value: f64) -> Result<u32, &'static str> { Ok(self.scale(value, u32::MAX as f64)? as u32) }
+
+	pub fn to_key64(&self, value: f64) -> Result<u64, &'static str> { Ok(self.scale(value, u64::MAX as f64)? as u64) }
+
+	// clamps to the key range, with "NaN" mapping to 0
+	pub fn to_key_saturating(&self, value: f64) -> u32 { self.rounding.apply(value * self.per_unit) as u32 }
+
+	pub fn to_key64_saturating(&self, value: f64) -> u64 { self.rounding.apply(value * self.per_unit) as u64 }
+
+	// value "key" stands for; with "Floor" the smallest value mapping to it
+	pub fn to_value(&self, key: u64) -> f64 { key as f64 / self.per_unit }
+
+	// "duration" in seconds
+	pub fn duration_to_key(&self, duration: Duration) -> Result<u32, &'static str> { self.to_key(duration.as_secs_f64()) }
+
+	pub fn key_to_duration(&self, key: u64) -> Duration { Duration::from_secs_f64(self.to_value(key)) }
+}
+
+// maps priorities of any numeric type onto keys: "origin" becomes key 0
+// and each "step" above it one more key. As "Rounding" is monotone,
+// "a <= b" implies "quantize(a) <= quantize(b)", and conversely keys
+// "quantize(a) < quantize(b)" imply "a < b"; so popping quantized keys
+// never yields priorities out of order, only ties between nearby ones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantizer {
+	origin: f64,
+	scale: KeyScale
+}
+
+impl Quantizer {
+	pub fn new(origin: f64, step: f64, rounding: Rounding) -> Result<Quantizer, &'static str> {
+		if !origin.is_finite() { return Err("invalid origin"); }
+		Ok(Quantizer { origin, scale: KeyScale::with_resolution(step)?.rounded(rounding) })
+	}
+
+	pub fn quantize<T: Into<f64>>(&self, priority: T) -> Result<u32, &'static str> {
+		self.scale.to_key(priority.into() - self.origin)
+	}
+
+	pub fn quantize_saturating<T: Into<f64>>(&self, priority: T) -> u32 {
+		self.scale.to_key_saturating(priority.into() - self.origin)
+	}
+
+	pub fn dequantize(&self, key: u32) -> f64 { self.origin + self.scale.to_value(key as u64) }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_key_scale() {
+		assert_eq!(KeyScale::MICROS.to_key(1.5), Ok(1_500_000));
+		assert_eq!(KeyScale::MILLIS.to_key(0.0125), Ok(12));
+		assert_eq!(KeyScale::MICROS.to_key(4295.0), Err("value out of range"));
+		assert_eq!(KeyScale::MICROS.to_key64(4295.0), Ok(4_295_000_000));
+		assert_eq!(KeyScale::MILLIS.to_key(-0.5), Err("value out of range"));
+		assert_eq!(KeyScale::MILLIS.to_key(f64::NAN), Err("value is not a number"));
+
+		assert_eq!(KeyScale::MICROS.to_key_saturating(1e9), u32::MAX);
+		assert_eq!(KeyScale::MICROS.to_key_saturating(-3.0), 0);
+		assert_eq!(KeyScale::NANOS.to_key64_saturating(f64::INFINITY), u64::MAX);
+
+		let tenths = KeyScale::with_resolution(0.1).unwrap();
+		assert_eq!(tenths.to_key(2.75), Ok(27));
+		assert_eq!(tenths.duration_to_key(Duration::from_millis(2750)), Ok(27));
+		assert_eq!(KeyScale::MILLIS.key_to_duration(1500), Duration::from_millis(1500));
+		assert_eq!(KeyScale::new(0.0), Err("invalid resolution"));
+		assert_eq!(KeyScale::with_resolution(0.0), Err("invalid resolution"));
+	}
+
+	#[test]
+	fn test_quantizer() {
+		let keys = |rounding| {
+			let q = Quantizer::new(-10.0, 0.5, rounding).unwrap();
+			[-10.0, -9.8, -9.75, -9.6, 0.0, 3.3].iter().map(|&p: &f64| q.quantize(p).unwrap()).collect::<Vec<u32>>()
+		};
+
+		assert_eq!(keys(Rounding::Floor), vec![0, 0, 0, 0, 20, 26]);
+		assert_eq!(keys(Rounding::Ceil), vec![0, 1, 1, 1, 20, 27]);
+		assert_eq!(keys(Rounding::Nearest), vec![0, 0, 1, 1, 20, 27]);
+
+		let q = Quantizer::new(0.0, 1.0, Rounding::Nearest).unwrap();
+		assert_eq!(q.quantize(7u8), Ok(7));
+		assert_eq!(q.quantize(-1i32), Err("value out of range"));
+		assert_eq!(q.quantize_saturating(-1i32), 0);
+		assert_eq!(q.quantize(2.5f32), Ok(3));
+		assert_eq!(Quantizer::new(-10.0, 0.5, Rounding::Ceil).unwrap().dequantize(3), -8.5);
+		assert_eq!(Quantizer::new(f64::NAN, 1.0, Rounding::Floor), Err("invalid origin"));
+
+		// ordering survives quantization in every mode
+		for rounding in [Rounding::Floor, Rounding::Ceil, Rounding::Nearest] {
+			let q = Quantizer::new(0.0, 0.3, rounding).unwrap();
+			let keys: Vec<u32> = (0..1000).map(|i| q.quantize(i as f64 * 0.07).unwrap()).collect();
+			assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+		}
+	}
+}
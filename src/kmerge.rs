@@ -0,0 +1,91 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: kmerge.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use crate::RadixHeap;
+
+pub struct KMerge<I: Iterator<Item = (u32, V)>, V> {
+	sources: Vec<I>,
+	heads: Vec<Option<V>>,
+	// holds the key of the current head of every non-exhausted source
+	heap: RadixHeap<usize>
+}
+
+// merges ascending "(key, value)" streams into a single ascending stream;
+// panics if one of the streams turns out not to be sorted
+pub fn kmerge<S, V>(sources: S) -> KMerge<<S::Item as IntoIterator>::IntoIter, V>
+	where S: IntoIterator, S::Item: IntoIterator<Item = (u32, V)> {
+	let mut sources: Vec<_> = sources.into_iter().map(|s| s.into_iter()).collect();
+	let mut heads: Vec<Option<V>> = Vec::with_capacity(sources.len());
+	let mut heap = RadixHeap::new();
+
+	for (index, source) in sources.iter_mut().enumerate() {
+		if let Some((key, val)) = source.next() {
+			heap.push(key, index).unwrap();
+			heads.push(Some(val));
+		} else { heads.push(None); }
+	}
+
+	KMerge { sources, heads, heap }
+}
+
+impl<I: Iterator<Item = (u32, V)>, V> Iterator for KMerge<I, V> {
+	type Item = (u32, V);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (key, index) = self.heap.pop()?;
+		let val = self.heads[index].take().unwrap();
+
+		// refill the head of the source just consumed from
+		if let Some((next, v)) = self.sources[index].next() {
+			self.heap.push(next, index).expect("kmerge input is not sorted");
+			self.heads[index] = Some(v);
+		}
+
+		Some((key, val))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_kmerge() {
+		let merged: Vec<(u32, &str)> = kmerge(vec![
+			vec![(1, "a"), (4, "d"), (9, "i")],
+			vec![],
+			vec![(2, "b"), (3, "c"), (10, "j")],
+			vec![(5, "e")]
+		]).collect();
+
+		assert_eq!(merged.iter().map(|&(k, _)| k).collect::<Vec<u32>>(), vec![1, 2, 3, 4, 5, 9, 10]);
+		assert_eq!(merged.iter().map(|&(_, v)| v).collect::<String>(), "abcdeij");
+	}
+
+	#[test]
+	#[should_panic(expected = "kmerge input is not sorted")]
+	fn test_kmerge_unsorted() {
+		kmerge(vec![vec![(3, ()), (1, ())], vec![(2, ())]]).for_each(drop);
+	}
+}
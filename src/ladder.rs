@@ -0,0 +1,222 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: ladder.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+// buckets holding more items than this are split into a new rung
+const THRESHOLD: usize = 50;
+const MAX_RUNGS: usize = 8;
+
+#[derive(Clone, Debug)]
+struct Rung<V> {
+	buckets: Vec<Vec<(u32, V)>>,
+	start: u64,
+	width: u64,
+	// first bucket not yet handed down to the bottom
+	current: usize
+}
+
+impl<V> Rung<V> {
+	fn current_start(&self) -> u64 { self.start + self.current as u64 * self.width }
+}
+
+// ladder queue: unsorted events far in the future are collected in "top",
+// spread over rungs of increasingly fine buckets once needed, and only the
+// events of the earliest bucket are sorted into "bottom"; skewed key
+// distributions simply spawn more rungs where events cluster
+#[derive(Clone, Debug)]
+pub struct LadderQueue<V> {
+	top: Vec<(u32, V)>,
+	top_start: u64,
+	top_min: u32,
+	top_max: u32,
+	rungs: Vec<Rung<V>>,
+	// sorted in descending order
+	bottom: Vec<(u32, V)>,
+	bound: u32,
+	length: usize
+}
+
+impl<V> LadderQueue<V> {
+	pub fn new() -> LadderQueue<V> {
+		LadderQueue {
+			top: Vec::new(),
+			top_start: 0,
+			top_min: u32::MAX,
+			top_max: 0,
+			rungs: Vec::new(),
+			bottom: Vec::new(),
+			bound: 0,
+			length: 0
+		}
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	// smallest key "push" currently accepts
+	pub fn bound(&self) -> u32 { self.bound }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.bound { return Err("key too small"); }
+
+		self.length += 1;
+		if u64::from(key) >= self.top_start {
+			self.top_min = self.top_min.min(key);
+			self.top_max = self.top_max.max(key);
+			self.top.push((key, val));
+			return Ok(());
+		}
+
+		// coarsest rung whose unconsumed range covers the key
+		if let Some(rung) = self.rungs.iter_mut().find(|r| u64::from(key) >= r.current_start()) {
+			let index = ((u64::from(key) - rung.start) / rung.width) as usize;
+			rung.buckets[index].push((key, val));
+		} else {
+			let position = self.bottom.partition_point(|&(k, _)| k > key);
+			self.bottom.insert(position, (key, val));
+		}
+
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		if self.bottom.is_empty() { self.refill(); }
+
+		let (key, val) = self.bottom.pop()?;
+		self.bound = key;
+		self.length -= 1;
+		Some((key, val))
+	}
+
+	pub fn clear(&mut self) {
+		self.top.clear();
+		self.rungs.clear();
+		self.bottom.clear();
+		self.top_min = u32::MAX;
+		self.top_max = 0;
+		self.length = 0;
+	}
+
+	// moves the earliest events into the bottom
+	fn refill(&mut self) {
+		loop {
+			let rung = match self.rungs.last_mut() {
+				Some(rung) => rung,
+				None if self.top.is_empty() => return,
+				None => {
+					self.spread_top();
+					continue;
+				}
+			};
+
+			let Some(index) = (rung.current..rung.buckets.len()).find(|&i| !rung.buckets[i].is_empty()) else {
+				self.rungs.pop();
+				continue;
+			};
+
+			rung.current = index + 1;
+			let bucket = std::mem::take(&mut rung.buckets[index]);
+			let start = rung.start + index as u64 * rung.width;
+			let width = rung.width;
+
+			if bucket.len() <= THRESHOLD || width == 1 || self.rungs.len() == MAX_RUNGS {
+				self.bottom = bucket;
+				self.bottom.sort_by_key(|&(k, _)| std::cmp::Reverse(k));
+				return;
+			}
+
+			let child = width.div_ceil(bucket.len() as u64);
+			self.rungs.push(Rung::spread(bucket, start, child, width.div_ceil(child) as usize));
+		}
+	}
+
+	fn spread_top(&mut self) {
+		let items = std::mem::take(&mut self.top);
+		let (min, max) = (u64::from(self.top_min), u64::from(self.top_max));
+		let width = (max - min) / items.len() as u64 + 1;
+		let count = ((max - min) / width + 1) as usize;
+
+		self.top_start = min + count as u64 * width;
+		self.top_min = u32::MAX;
+		self.top_max = 0;
+		self.rungs.push(Rung::spread(items, min, width, count));
+	}
+}
+
+impl<V> Rung<V> {
+	fn spread(items: Vec<(u32, V)>, start: u64, width: u64, count: usize) -> Rung<V> {
+		let mut buckets: Vec<Vec<(u32, V)>> = (0..count).map(|_| Vec::new()).collect();
+		for (key, val) in items { buckets[((u64::from(key) - start) / width) as usize].push((key, val)); }
+		Rung { buckets, start, width, current: 0 }
+	}
+}
+
+impl<V: Clone> LadderQueue<V> {
+	pub fn peek(&self) -> Option<(u32, V)> {
+		if let Some(item) = self.bottom.last() { return Some(item.clone()); }
+
+		// the finest rung holding anything holds the earliest events; among
+		// equal keys, the last one is handed out first once sorted
+		for rung in self.rungs.iter().rev() {
+			if let Some(bucket) = rung.buckets[rung.current..].iter().find(|b| !b.is_empty()) {
+				return bucket.iter().rev().min_by_key(|&&(k, _)| k).cloned();
+			}
+		}
+
+		self.top.iter().rev().min_by_key(|&&(k, _)| k).cloned()
+	}
+}
+
+impl<V> Default for LadderQueue<V> {
+	fn default() -> LadderQueue<V> { LadderQueue::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_ladder_queue() {
+		let mut queue = LadderQueue::new();
+		let mut rng = rand::thread_rng();
+		let mut popped = Vec::new();
+
+		// heavily skewed: most events cluster right after the current time
+		for i in 0..2000u32 {
+			let key = if i % 10 == 0 { rng.gen_range(0, 1 << 30) } else { rng.gen_range(0, 1000) };
+			queue.push(key, i).unwrap();
+		}
+
+		while let Some((time, i)) = queue.peek() {
+			assert_eq!(queue.pop(), Some((time, i)));
+			popped.push(time);
+			if i < 6000 { queue.push(time + rng.gen_range(0, 50), i + 2000).unwrap(); }
+		}
+
+		assert_eq!(popped.len(), 8000);
+		assert!(popped.windows(2).all(|w| w[0] <= w[1]));
+		assert_eq!(queue.push(popped[0], 0), Err("key too small"));
+	}
+}
@@ -0,0 +1,98 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: mst.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use crate::graph::Graph;
+use crate::RadixHeap;
+
+// Prim's frontier is not monotone (an edge lighter than the last extracted
+// one may become reachable later), so it cannot be driven by a radix heap.
+// All edges are pushed up front instead and extracted in ascending order,
+// which is exactly the order Kruskal's algorithm consumes them in.
+
+fn find(parent: &mut [usize], node: usize) -> usize {
+	let mut root = node;
+	while parent[root] != root { root = parent[root]; }
+
+	// path compression
+	let mut current = node;
+	while parent[current] != root {
+		let next = parent[current];
+		parent[current] = root;
+		current = next;
+	}
+
+	root
+}
+
+// returns the edges "(u, v, weight)" of a minimum spanning forest of the
+// undirected graph, e.g. given as adjacency lists, together with its total
+// weight
+pub fn minimum_spanning_tree<G: Graph + ?Sized>(graph: &G) -> (Vec<(usize, usize, u32)>, u64) {
+	let mut heap: RadixHeap<(usize, usize)> = RadixHeap::new();
+	let mut parent: Vec<usize> = (0..graph.node_count()).collect();
+	let mut edges: Vec<(usize, usize, u32)> = Vec::new();
+	let mut total: u64 = 0;
+
+	for u in 0..graph.node_count() {
+		for (v, weight) in graph.edges(u) { heap.push(weight, (u, v)).unwrap(); }
+	}
+
+	while let Some((weight, (u, v))) = heap.pop() {
+		let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+		if ru == rv { continue; }
+
+		parent[ru] = rv;
+		edges.push((u, v, weight));
+		total += u64::from(weight);
+
+		if edges.len() + 1 == graph.node_count() { break; }
+	}
+
+	(edges, total)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_mst() {
+		// both directions of each undirected edge are listed
+		let graph = vec![
+			vec![(1, 5), (2, 1)],
+			vec![(0, 5), (2, 1), (3, 7)],
+			vec![(0, 1), (1, 1), (3, 9)],
+			vec![(1, 7), (2, 9)]
+		];
+
+		let (edges, total) = minimum_spanning_tree(&graph);
+		assert_eq!(total, 9);
+		assert_eq!(edges.len(), 3);
+		assert!(edges.contains(&(1, 3, 7)) || edges.contains(&(3, 1, 7)));
+
+		let (edges, total) = minimum_spanning_tree(&[vec![], vec![]]);
+		assert!(edges.is_empty());
+		assert_eq!(total, 0);
+	}
+}
@@ -0,0 +1,181 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: multiqueue.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use crate::RadixHeap;
+use crate::sync::thread_index;
+
+const EMPTY: u64 = u64::MAX;
+
+thread_local! {
+	static STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+// xorshift generator per thread, seeded from the thread identity
+fn random() -> usize {
+	STATE.with(|state| {
+		let mut x = state.get();
+		if x == 0 { x = (thread_index(usize::MAX) as u64) | 1; }
+
+		x ^= x << 13;
+		x ^= x >> 7;
+		x ^= x << 17;
+		state.set(x);
+		x as usize
+	})
+}
+
+// MultiQueue as used by parallel shortest path algorithms: items are pushed
+// into random lanes, and a pop takes the better top of two random lanes.
+// Popped items are close to, but not necessarily the global minimum.
+pub struct MultiQueue<V: Clone + Debug + Ord> {
+	lanes: Vec<Mutex<RadixHeap<V>>>,
+	tops: Vec<AtomicU64>,
+	bounds: Vec<AtomicU32>,
+	length: AtomicUsize
+}
+
+impl<V: Clone + Debug + Ord> MultiQueue<V> {
+	// "threads * factor" lanes, the literature suggests a factor of 2 to 4
+	pub fn new(threads: usize, factor: usize) -> MultiQueue<V> {
+		let lanes = (threads * factor).max(2);
+
+		MultiQueue {
+			lanes: (0..lanes).map(|_| Mutex::new(RadixHeap::new())).collect(),
+			tops: (0..lanes).map(|_| AtomicU64::new(EMPTY)).collect(),
+			bounds: (0..lanes).map(|_| AtomicU32::new(0)).collect(),
+			length: AtomicUsize::new(0)
+		}
+	}
+
+	pub fn lanes(&self) -> usize { self.lanes.len() }
+
+	pub fn len(&self) -> usize { self.length.load(Ordering::SeqCst) }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> {
+		let count = self.lanes.len();
+		let start = random() % count;
+
+		// random lanes first, then every lane whose bound admits the key
+		for attempt in 0..(2 * count) {
+			let i = if attempt < count { random() } else { start + attempt } % count;
+			if self.bounds[i].load(Ordering::SeqCst) > key { continue; }
+
+			let lane = if attempt < count { self.lanes[i].try_lock().ok() } else { self.lanes[i].lock().ok() };
+			if let Some(mut lane) = lane {
+				if lane.push(key, val.clone()).is_err() { continue; }
+
+				self.length.fetch_add(1, Ordering::SeqCst);
+				self.publish(i, &lane);
+				return Ok(());
+			}
+		}
+
+		Err("key too small")
+	}
+
+	pub fn pop(&self) -> Option<(u32, V)> {
+		let count = self.lanes.len();
+
+		while !self.is_empty() {
+			// two random choices, the better one wins
+			let (a, b) = (random() % count, random() % count);
+			let i = if self.top(a) <= self.top(b) { a } else { b };
+
+			if self.top(i) != EMPTY {
+				if let Ok(mut lane) = self.lanes[i].try_lock() {
+					if let Some(item) = self.take(i, &mut lane) { return Some(item); }
+				}
+				continue;
+			}
+
+			// both choices were empty, look for any item left
+			for i in 0..count {
+				if self.top(i) == EMPTY { continue; }
+				if let Some(item) = self.take(i, &mut self.lanes[i].lock().unwrap()) { return Some(item); }
+			}
+		}
+
+		None
+	}
+
+	fn top(&self, lane: usize) -> u64 { self.tops[lane].load(Ordering::SeqCst) }
+
+	fn take(&self, i: usize, lane: &mut MutexGuard<'_, RadixHeap<V>>) -> Option<(u32, V)> {
+		let item = lane.pop()?;
+
+		self.length.fetch_sub(1, Ordering::SeqCst);
+		self.bounds[i].store(item.0, Ordering::SeqCst);
+		self.publish(i, lane);
+		Some(item)
+	}
+
+	fn publish(&self, lane: usize, heap: &MutexGuard<'_, RadixHeap<V>>) {
+		let top = heap.peek_key().map_or(EMPTY, u64::from);
+		self.tops[lane].store(top, Ordering::SeqCst);
+	}
+}
+
+// shareable between threads as long as the values can be sent
+#[allow(dead_code)]
+const _: () = {
+	fn send_sync<T: Send + Sync>() {}
+	fn is_send_sync<V: Clone + Debug + Ord + Send>() { send_sync::<MultiQueue<V>>(); }
+};
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::Arc;
+	use std::thread;
+
+	#[test]
+	fn test_multiqueue() {
+		let queue: Arc<MultiQueue<u32>> = Arc::new(MultiQueue::new(4, 2));
+		for key in 0..2000u32 { queue.push(key, key).unwrap(); }
+		assert_eq!(queue.lanes(), 8);
+
+		let workers: Vec<_> = (0..4).map(|_| {
+			let queue = Arc::clone(&queue);
+			thread::spawn(move || {
+				let mut popped = Vec::new();
+				while let Some((key, val)) = queue.pop() {
+					assert_eq!(key, val);
+					popped.push(key);
+				}
+				popped
+			})
+		}).collect();
+
+		let mut all: Vec<u32> = workers.into_iter().flat_map(|w| w.join().unwrap()).collect();
+		all.sort_unstable();
+		assert_eq!(all, (0..2000).collect::<Vec<u32>>());
+		assert!(queue.is_empty());
+	}
+}
@@ -0,0 +1,178 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: pacer.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use crate::clock::{Clock, ManualClock};
+use crate::RadixHeap;
+
+#[derive(Clone, Copy, Debug)]
+struct Flow {
+	interval: u32,
+	// earliest time the next token of the flow may be released at
+	next: u32
+}
+
+// schedules token releases per flow, spacing the releases of every flow
+// at least "interval" ticks apart
+#[derive(Clone, Debug)]
+pub struct Pacer<K: Clone + Debug + Ord + Hash, C: Clock = ManualClock> {
+	heap: RadixHeap<K>,
+	flows: HashMap<K, Flow>,
+	now: u32,
+	clock: C
+}
+
+impl<K: Clone + Debug + Ord + Hash> Pacer<K> {
+	pub fn new() -> Pacer<K> { Pacer::with_clock(ManualClock::new()) }
+}
+
+impl<K: Clone + Debug + Ord + Hash, C: Clock> Pacer<K, C> {
+	pub fn with_clock(clock: C) -> Pacer<K, C> {
+		let now = clock.now();
+		Pacer { heap: RadixHeap::new(), flows: HashMap::new(), now, clock }
+	}
+
+	pub fn clock(&self) -> &C { &self.clock }
+
+	pub fn now(&self) -> u32 { self.now }
+
+	// number of scheduled but not yet released tokens
+	pub fn pending(&self) -> usize { self.heap.len() }
+
+	// adds a flow or changes the interval of an existing one
+	pub fn set_interval(&mut self, flow: K, interval: u32) {
+		let now = self.now;
+		self.flows.entry(flow).or_insert(Flow { interval, next: now }).interval = interval;
+	}
+
+	// removes a flow together with its releases that are still queued
+	pub fn remove_flow(&mut self, flow: &K) -> bool {
+		if self.flows.remove(flow).is_none() { return false; }
+
+		// keeping the bound, every release lands in the bucket it came from
+		let mut releases = self.heap.tuples();
+		releases.retain(|(_, k)| k != flow);
+
+		self.heap = RadixHeap::with_bound(self.heap.bound());
+		for (release, k) in releases { self.heap.push(release, k).unwrap(); }
+		true
+	}
+
+	// schedules the next token release of a flow and returns its time
+	pub fn schedule(&mut self, flow: &K) -> Result<u32, &'static str> {
+		let now = self.now;
+		let state = self.flows.get_mut(flow).ok_or("unknown flow")?;
+		let release = state.next.max(now);
+
+		state.next = release.checked_add(state.interval).ok_or("release time overflow")?;
+		self.heap.push(release, flow.clone()).map_err(|_| "release time overflow")?;
+		Ok(release)
+	}
+
+	pub fn next_release(&self) -> Option<u32> { self.heap.peek_key() }
+
+	// moves the clock forward and returns all token releases due until then
+	pub fn advance(&mut self, now: u32) -> Vec<(u32, K)> {
+		let mut released = Vec::new();
+		if now > self.now { self.now = now; }
+
+		while self.next_release().is_some_and(|t| t <= self.now) {
+			released.push(self.heap.pop().unwrap());
+		}
+
+		released
+	}
+
+	// advances to the current time of the clock
+	pub fn tick(&mut self) -> Vec<(u32, K)> {
+		let now = self.clock.now();
+		self.advance(now)
+	}
+}
+
+impl<K: Clone + Debug + Ord + Hash> Default for Pacer<K> {
+	fn default() -> Pacer<K> { Pacer::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_pacer() {
+		let mut pacer = Pacer::new();
+		pacer.set_interval("api", 10);
+		pacer.set_interval("bulk", 25);
+
+		assert_eq!(pacer.schedule(&"api"), Ok(0));
+		assert_eq!(pacer.schedule(&"api"), Ok(10));
+		assert_eq!(pacer.schedule(&"api"), Ok(20));
+		assert_eq!(pacer.schedule(&"bulk"), Ok(0));
+		assert_eq!(pacer.schedule(&"bulk"), Ok(25));
+		assert_eq!(pacer.schedule(&"unknown"), Err("unknown flow"));
+
+		assert_eq!(pacer.advance(0).len(), 2);
+		assert_eq!(pacer.next_release(), Some(10));
+		assert_eq!(pacer.advance(24), vec![(10, "api"), (20, "api")]);
+		assert_eq!(pacer.pending(), 1);
+
+		// an idle flow does not accumulate tokens
+		assert_eq!(pacer.advance(100), vec![(25, "bulk")]);
+		assert_eq!(pacer.schedule(&"api"), Ok(100));
+	}
+
+	#[test]
+	fn test_remove_flow() {
+		let mut pacer = Pacer::new();
+		pacer.set_interval("api", 10);
+		pacer.set_interval("bulk", 25);
+
+		for _ in 0..3 { pacer.schedule(&"bulk").unwrap(); }
+		pacer.schedule(&"api").unwrap();
+		pacer.schedule(&"api").unwrap();
+		assert_eq!(pacer.advance(0).len(), 2);
+
+		assert!(pacer.remove_flow(&"bulk"));
+		assert!(!pacer.remove_flow(&"bulk"));
+		assert_eq!((pacer.pending(), pacer.next_release()), (1, Some(10)));
+		assert_eq!(pacer.advance(100), vec![(10, "api")]);
+		assert_eq!(pacer.next_release(), None);
+	}
+
+	#[test]
+	fn test_pacer_clock() {
+		let clock = ManualClock::new();
+		clock.set(50);
+
+		let mut pacer = Pacer::with_clock(clock.clone());
+		pacer.set_interval("api", 10);
+		assert_eq!((pacer.schedule(&"api"), pacer.schedule(&"api")), (Ok(50), Ok(60)));
+
+		clock.advance(5);
+		assert_eq!(pacer.tick(), vec![(50, "api")]);
+		assert_eq!(pacer.now(), 55);
+	}
+}
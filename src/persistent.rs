@@ -0,0 +1,199 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: persistent.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::sync::Arc;
+
+struct Node<V> {
+	key: u32,
+	val: V,
+	next: Option<Arc<Node<V>>>
+}
+
+// immutable singly linked bucket shared between heap versions
+struct List<V> {
+	head: Option<Arc<Node<V>>>,
+	length: usize,
+	min: u32
+}
+
+impl<V> List<V> {
+	fn new() -> List<V> { List { head: None, length: 0, min: u32::MAX } }
+
+	fn cons(&self, key: u32, val: V) -> List<V> {
+		List {
+			head: Some(Arc::new(Node { key, val, next: self.head.clone() })),
+			length: self.length + 1,
+			min: self.min.min(key)
+		}
+	}
+
+	fn iter(&self) -> impl Iterator<Item = &Node<V>> {
+		std::iter::successors(self.head.as_deref(), |n| n.next.as_deref())
+	}
+}
+
+impl<V> Clone for List<V> {
+	fn clone(&self) -> List<V> { List { head: self.head.clone(), length: self.length, min: self.min } }
+}
+
+// unlinks nodes one by one instead of recursively, so dropping a long
+// bucket cannot overflow the stack
+impl<V> Drop for List<V> {
+	fn drop(&mut self) {
+		let mut head = self.head.take();
+
+		while let Some(node) = head {
+			head = match Arc::try_unwrap(node) {
+				Ok(mut node) => node.next.take(),
+				Err(_) => None
+			};
+		}
+	}
+}
+
+// radix heap whose "push" and "pop" leave the heap untouched and return a
+// new version instead; versions share all buckets they did not change, so
+// keeping old versions around is cheap
+pub struct PersistentRadixHeap<V> {
+	buckets: Vec<List<V>>,
+	toplast: u32,
+	length: usize
+}
+
+impl<V> PersistentRadixHeap<V> {
+	pub fn new() -> PersistentRadixHeap<V> {
+		PersistentRadixHeap { buckets: (0..33).map(|_| List::new()).collect(), toplast: 0, length: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	fn bucket_index(&self, key: u32) -> usize {
+		if key == self.toplast { 0 } else { 32 - (key ^ self.toplast).leading_zeros() as usize }
+	}
+
+	pub fn push(&self, key: u32, val: V) -> Result<PersistentRadixHeap<V>, &'static str> {
+		if key < self.toplast { return Err("key too small"); }
+
+		let mut heap = self.clone();
+		let index = heap.bucket_index(key);
+		heap.buckets[index] = heap.buckets[index].cons(key, val);
+		heap.length += 1;
+		Ok(heap)
+	}
+
+	pub fn peek(&self) -> Option<(u32, &V)> {
+		let bucket = self.buckets.iter().find(|b| b.length > 0)?;
+		bucket.iter().find(|n| n.key == bucket.min).map(|n| (n.key, &n.val))
+	}
+}
+
+impl<V: Clone> PersistentRadixHeap<V> {
+	// returns the priority element together with the heap remaining
+	// without it
+	pub fn pop(&self) -> Option<((u32, V), PersistentRadixHeap<V>)> {
+		let index = self.buckets.iter().position(|b| b.length > 0)?;
+		let mut heap = self.clone();
+		heap.length -= 1;
+
+		if index == 0 {
+			// all keys of bucket 0 are equal
+			let node = self.buckets[0].head.as_deref().unwrap();
+			heap.buckets[0] = List {
+				head: node.next.clone(),
+				length: self.buckets[0].length - 1,
+				min: if self.buckets[0].length > 1 { node.key } else { u32::MAX }
+			};
+			return Some(((node.key, node.val.clone()), heap));
+		}
+
+		let bucket = &self.buckets[index];
+		heap.toplast = bucket.min;
+		heap.buckets[index] = List::new();
+
+		let mut top = None;
+		for node in bucket.iter() {
+			if top.is_none() && node.key == bucket.min { top = Some((node.key, node.val.clone())); } else {
+				let to = heap.bucket_index(node.key);
+				heap.buckets[to] = heap.buckets[to].cons(node.key, node.val.clone());
+			}
+		}
+
+		top.map(|t| (t, heap))
+	}
+}
+
+impl<V> Clone for PersistentRadixHeap<V> {
+	fn clone(&self) -> PersistentRadixHeap<V> {
+		PersistentRadixHeap { buckets: self.buckets.clone(), toplast: self.toplast, length: self.length }
+	}
+}
+
+impl<V> Default for PersistentRadixHeap<V> {
+	fn default() -> PersistentRadixHeap<V> { PersistentRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_persistent() {
+		let empty = PersistentRadixHeap::new();
+		let one = empty.push(7, "g").unwrap();
+		let three = one.push(3, "c").unwrap().push(12, "l").unwrap();
+
+		let ((key, val), rest) = three.pop().unwrap();
+		assert_eq!((key, val), (3, "c"));
+		assert!(rest.push(2, "b").is_err());
+
+		// older versions are unaffected by everything derived from them
+		assert_eq!(three.len(), 3);
+		assert_eq!(three.peek(), Some((3, &"c")));
+		assert_eq!(one.peek(), Some((7, &"g")));
+		assert!(empty.pop().is_none());
+
+		// branching off the same version
+		let left = rest.push(5, "e").unwrap();
+		let right = rest.push(9, "i").unwrap();
+		assert_eq!(left.pop().unwrap().0, (5, "e"));
+		assert_eq!(right.pop().unwrap().0, (7, "g"));
+
+		let mut heap = three.push(3, "x").unwrap();
+		let mut keys = Vec::new();
+		while let Some(((k, _), next)) = heap.pop() {
+			keys.push(k);
+			heap = next;
+		}
+		assert_eq!(keys, vec![3, 3, 7, 12]);
+	}
+
+	#[test]
+	fn test_long_bucket() {
+		let mut heap = PersistentRadixHeap::new();
+		for _ in 0..200000 { heap = heap.push(1u32 << 31, ()).unwrap(); }
+		assert_eq!(heap.len(), 200000);
+	}
+}
@@ -0,0 +1,149 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: queue.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use core::fmt::Debug;
+#[cfg(feature = "std")]
+use crate::bucket_queue::BucketQueue;
+#[cfg(feature = "std")]
+use crate::calendar::CalendarQueue;
+#[cfg(feature = "std")]
+use crate::ladder::LadderQueue;
+#[cfg(feature = "std")]
+use crate::rle::RleRadixHeap;
+#[cfg(feature = "std")]
+use crate::sorted::SortedRadixHeap;
+use crate::static_heap::StaticRadixHeap;
+#[cfg(feature = "std")]
+use crate::wide::WideRadixHeap;
+#[cfg(feature = "std")]
+use crate::RadixHeap;
+
+// common interface of all monotone priority queues of this crate, so code
+// can switch between them depending on the key distribution at hand
+pub trait MonotonePriorityQueue<V> {
+	// fails for keys smaller than "bound()"
+	fn push(&mut self, key: u32, val: V) -> Result<(), &'static str>;
+	fn pop(&mut self) -> Option<(u32, V)>;
+	fn peek(&self) -> Option<(u32, V)>;
+	fn len(&self) -> usize;
+	fn is_empty(&self) -> bool { self.len() == 0 }
+	fn bound(&self) -> u32;
+	fn clear(&mut self);
+}
+
+#[cfg(feature = "std")]
+macro_rules! monotone_priority_queue {
+	($t:ident, $($bound:tt)*) => {
+		impl<V: $($bound)*> MonotonePriorityQueue<V> for $t<V> {
+			fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { $t::push(self, key, val) }
+			fn pop(&mut self) -> Option<(u32, V)> { $t::pop(self) }
+			fn peek(&self) -> Option<(u32, V)> { $t::peek(self) }
+			fn len(&self) -> usize { $t::len(self) }
+			fn is_empty(&self) -> bool { $t::is_empty(self) }
+			fn bound(&self) -> u32 { $t::bound(self) }
+			fn clear(&mut self) { $t::clear(self) }
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+monotone_priority_queue!(RadixHeap, Clone + Debug + Ord);
+#[cfg(feature = "std")]
+monotone_priority_queue!(BucketQueue, Clone);
+#[cfg(feature = "std")]
+monotone_priority_queue!(CalendarQueue, Clone);
+#[cfg(feature = "std")]
+monotone_priority_queue!(LadderQueue, Clone);
+
+impl<V: Clone + Debug + Ord, const N: usize> MonotonePriorityQueue<V> for StaticRadixHeap<V, N> {
+	fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { StaticRadixHeap::push(self, key, val) }
+	fn pop(&mut self) -> Option<(u32, V)> { StaticRadixHeap::pop(self) }
+	fn peek(&self) -> Option<(u32, V)> { StaticRadixHeap::peek(self).map(|(k, v)| (k, v.clone())) }
+	fn len(&self) -> usize { StaticRadixHeap::len(self) }
+	fn is_empty(&self) -> bool { StaticRadixHeap::is_empty(self) }
+	fn bound(&self) -> u32 { StaticRadixHeap::bound(self) }
+	fn clear(&mut self) { StaticRadixHeap::clear(self) }
+}
+
+#[cfg(feature = "std")]
+impl<V: Clone> MonotonePriorityQueue<V> for RleRadixHeap<V> {
+	fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { RleRadixHeap::push(self, key, val) }
+	fn pop(&mut self) -> Option<(u32, V)> { RleRadixHeap::pop(self) }
+	fn peek(&self) -> Option<(u32, V)> { RleRadixHeap::peek(self).map(|(k, v)| (k, v.clone())) }
+	fn len(&self) -> usize { RleRadixHeap::len(self) }
+	fn is_empty(&self) -> bool { RleRadixHeap::is_empty(self) }
+	fn bound(&self) -> u32 { RleRadixHeap::bound(self) }
+	fn clear(&mut self) { RleRadixHeap::clear(self) }
+}
+
+#[cfg(feature = "std")]
+impl<V: Clone> MonotonePriorityQueue<V> for SortedRadixHeap<V> {
+	fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { SortedRadixHeap::push(self, key, val) }
+	fn pop(&mut self) -> Option<(u32, V)> { SortedRadixHeap::pop(self) }
+	fn peek(&self) -> Option<(u32, V)> { SortedRadixHeap::peek(self).map(|(k, v)| (k, v.clone())) }
+	fn len(&self) -> usize { SortedRadixHeap::len(self) }
+	fn is_empty(&self) -> bool { SortedRadixHeap::is_empty(self) }
+	fn bound(&self) -> u32 { SortedRadixHeap::bound(self) }
+	fn clear(&mut self) { SortedRadixHeap::clear(self) }
+}
+
+#[cfg(feature = "std")]
+impl<V: Clone, const BITS: u32> MonotonePriorityQueue<V> for WideRadixHeap<V, BITS> {
+	fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> { WideRadixHeap::push(self, key, val) }
+	fn pop(&mut self) -> Option<(u32, V)> { WideRadixHeap::pop(self) }
+	fn peek(&self) -> Option<(u32, V)> { WideRadixHeap::peek(self) }
+	fn len(&self) -> usize { WideRadixHeap::len(self) }
+	fn is_empty(&self) -> bool { WideRadixHeap::is_empty(self) }
+	fn bound(&self) -> u32 { WideRadixHeap::bound(self) }
+	fn clear(&mut self) { WideRadixHeap::clear(self) }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+
+	fn drain<Q: MonotonePriorityQueue<char>>(mut queue: Q) -> String {
+		for (key, val) in [(5, 'c'), (1, 'a'), (3, 'b'), (9, 'd')] { queue.push(key, val).unwrap(); }
+		queue.pop();
+		assert!(queue.push(0, 'x').is_err());
+		assert_eq!(queue.peek(), Some((3, 'b')));
+
+		let mut out = String::new();
+		while let Some((_, val)) = queue.pop() { out.push(val); }
+		assert!(queue.is_empty());
+		out
+	}
+
+	#[test]
+	fn test_interchangeable() {
+		assert_eq!(drain(RadixHeap::new()), "bcd");
+		assert_eq!(drain(BucketQueue::new(10)), "bcd");
+		assert_eq!(drain(CalendarQueue::new()), "bcd");
+		assert_eq!(drain(LadderQueue::new()), "bcd");
+		assert_eq!(drain(StaticRadixHeap::<char, 4>::new()), "bcd");
+		assert_eq!(drain(WideRadixHeap::<char, 8>::new()), "bcd");
+		assert_eq!(drain(SortedRadixHeap::new()), "bcd");
+		assert_eq!(drain(RleRadixHeap::new()), "bcd");
+	}
+}
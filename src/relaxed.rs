@@ -0,0 +1,169 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: relaxed.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use crate::RadixHeap;
+use crate::sync::thread_index;
+
+const EMPTY: u64 = u64::MAX;
+
+// Experimental concurrent heap with relaxed ordering, split into lanes of
+// ordinary radix heaps. The minimum key and the bound of every lane are
+// published through atomics, so threads pick lanes without taking any
+// lock and only lock the lane they actually use. Lanes busy with other
+// threads are skipped, hence "pop" may return a near-minimum item, and a
+// key below the last popped one is still accepted as long as some lane's
+// bound allows it.
+pub struct RelaxedRadixHeap<V: Clone + Debug + Ord> {
+	lanes: Vec<Mutex<RadixHeap<V>>>,
+	tops: Vec<AtomicU64>,
+	bounds: Vec<AtomicU32>,
+	length: AtomicUsize
+}
+
+impl<V: Clone + Debug + Ord> RelaxedRadixHeap<V> {
+	pub fn new(lanes: usize) -> RelaxedRadixHeap<V> {
+		let lanes = lanes.max(1);
+
+		RelaxedRadixHeap {
+			lanes: (0..lanes).map(|_| Mutex::new(RadixHeap::new())).collect(),
+			tops: (0..lanes).map(|_| AtomicU64::new(EMPTY)).collect(),
+			bounds: (0..lanes).map(|_| AtomicU32::new(0)).collect(),
+			length: AtomicUsize::new(0)
+		}
+	}
+
+	pub fn lanes(&self) -> usize { self.lanes.len() }
+
+	pub fn len(&self) -> usize { self.length.load(Ordering::SeqCst) }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> {
+		let home = thread_index(self.lanes.len());
+		let count = self.lanes.len();
+
+		// prefer idle lanes, but block on a suitable one rather than fail
+		for blocking in &[false, true] {
+			for i in (0..count).map(|i| (home + i) % count) {
+				if self.bounds[i].load(Ordering::SeqCst) > key { continue; }
+
+				let lane = if *blocking { self.lanes[i].lock().ok() } else { self.lanes[i].try_lock().ok() };
+				if let Some(mut lane) = lane {
+					if lane.push(key, val.clone()).is_err() { continue; }
+
+					self.length.fetch_add(1, Ordering::SeqCst);
+					self.publish(i, &lane);
+					return Ok(());
+				}
+			}
+		}
+
+		Err("key too small")
+	}
+
+	// pops the minimum of the lowest lane that is not busy
+	pub fn pop(&self) -> Option<(u32, V)> {
+		let mut order: Vec<usize> = (0..self.lanes.len()).collect();
+
+		while !self.is_empty() {
+			order.sort_by_key(|&i| self.tops[i].load(Ordering::SeqCst));
+
+			for blocking in &[false, true] {
+				for &i in &order {
+					if self.tops[i].load(Ordering::SeqCst) == EMPTY { break; }
+
+					let lane = if *blocking { self.lanes[i].lock().ok() } else { self.lanes[i].try_lock().ok() };
+					if let Some(mut lane) = lane {
+						if let Some(top) = lane.pop() {
+							self.length.fetch_sub(1, Ordering::SeqCst);
+							self.bounds[i].store(top.0, Ordering::SeqCst);
+							self.publish(i, &lane);
+							return Some(top);
+						}
+					}
+				}
+			}
+		}
+
+		None
+	}
+
+	fn publish(&self, lane: usize, heap: &MutexGuard<'_, RadixHeap<V>>) {
+		let top = heap.peek_key().map_or(EMPTY, u64::from);
+		self.tops[lane].store(top, Ordering::SeqCst);
+	}
+}
+
+// shareable between threads as long as the values can be sent
+#[allow(dead_code)]
+const _: () = {
+	fn send_sync<T: Send + Sync>() {}
+	fn is_send_sync<V: Clone + Debug + Ord + Send>() { send_sync::<RelaxedRadixHeap<V>>(); }
+};
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::Arc;
+	use std::thread;
+
+	#[test]
+	fn test_sequential_exact() {
+		// without contention the relaxed heap is exact
+		let heap = RelaxedRadixHeap::new(4);
+		for key in &[40, 7, 19, 3, 88] { heap.push(*key, ()).unwrap(); }
+
+		let keys: Vec<u32> = (0..5).map(|_| heap.pop().unwrap().0).collect();
+		assert_eq!(keys, vec![3, 7, 19, 40, 88]);
+		assert_eq!(heap.pop(), None);
+	}
+
+	#[test]
+	fn test_concurrent() {
+		let heap: Arc<RelaxedRadixHeap<u32>> = Arc::new(RelaxedRadixHeap::new(4));
+		let pushed = Arc::new(AtomicUsize::new(1000));
+		for key in 0..1000u32 { heap.push(key, key).unwrap(); }
+
+		let consumers: Vec<_> = (0..4).map(|_| {
+			let (heap, pushed) = (Arc::clone(&heap), Arc::clone(&pushed));
+			thread::spawn(move || {
+				let mut popped = 0;
+				while let Some((key, _)) = heap.pop() {
+					popped += 1;
+					if key < 1000 && key % 10 == 0 && heap.push(key + 1000, key).is_ok() {
+						pushed.fetch_add(1, Ordering::SeqCst);
+					}
+				}
+				popped
+			})
+		}).collect();
+
+		let popped: usize = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+		assert_eq!(popped, pushed.load(Ordering::SeqCst));
+		assert!(heap.is_empty());
+	}
+}
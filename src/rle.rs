@@ -0,0 +1,177 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: rle.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::mem;
+
+// radix heap for keys with massive duplication, e.g. unit edge weights:
+// each bucket stores every distinct key once together with the run of
+// values pushed with it. Redistribution moves whole runs and popping all
+// items of the smallest key takes out a single run.
+#[derive(Clone, Debug)]
+pub struct RleRadixHeap<V> {
+	buckets: Vec<Vec<(u32, Vec<V>)>>,
+	toplast: u32,
+	length: usize
+}
+
+impl<V> RleRadixHeap<V> {
+	pub fn new() -> RleRadixHeap<V> {
+		RleRadixHeap { buckets: (0..33).map(|_| Vec::new()).collect(), toplast: 0, length: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	pub fn bound(&self) -> u32 { self.toplast }
+
+	// number of distinct keys stored
+	pub fn runs(&self) -> usize { self.buckets.iter().map(Vec::len).sum() }
+
+	fn bucket_index(&self, key: u32) -> usize {
+		if key == self.toplast { 0 } else { 32 - (key ^ self.toplast).leading_zeros() as usize }
+	}
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		self.extend_run(key, std::iter::once(val))
+	}
+
+	// appends all of "vals" to the run of "key"
+	pub fn extend_run<I: IntoIterator<Item = V>>(&mut self, key: u32, vals: I) -> Result<(), &'static str> {
+		if key < self.toplast { return Err("key too small"); }
+
+		// duplicates tend to arrive close together, so search from the back
+		let index = self.bucket_index(key);
+		let bucket = &mut self.buckets[index];
+		let run = match bucket.iter().rposition(|(k, _)| *k == key) {
+			Some(position) => &mut bucket[position].1,
+			None => {
+				bucket.push((key, Vec::new()));
+				&mut bucket.last_mut().unwrap().1
+			}
+		};
+
+		let before = run.len();
+		run.extend(vals);
+		self.length += run.len() - before;
+		if run.is_empty() { bucket.pop(); }
+		Ok(())
+	}
+
+	// moves the run of the smallest key into bucket 0
+	fn settle(&mut self) -> Option<()> {
+		let index = self.buckets.iter().position(|b| !b.is_empty())?;
+		if index == 0 { return Some(()); }
+
+		let mut runs = mem::take(&mut self.buckets[index]);
+		self.toplast = runs.iter().map(|&(k, _)| k).min().unwrap();
+
+		for (key, vals) in runs.drain(..) {
+			let to = self.bucket_index(key);
+			self.buckets[to].push((key, vals));
+		}
+
+		self.buckets[index] = runs;
+		Some(())
+	}
+
+	pub fn peek(&self) -> Option<(u32, &V)> {
+		let bucket = self.buckets.iter().find(|b| !b.is_empty())?;
+		let (key, vals) = bucket.iter().min_by_key(|(k, _)| *k)?;
+		Some((*key, vals.last()?))
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		self.settle()?;
+
+		let (key, vals) = &mut self.buckets[0][0];
+		let top = (*key, vals.pop().unwrap());
+		if vals.is_empty() { self.buckets[0].clear(); }
+
+		self.length -= 1;
+		Some(top)
+	}
+
+	// pops all items of the smallest key in the order they were pushed
+	pub fn pop_ties(&mut self) -> Option<(u32, Vec<V>)> {
+		self.settle()?;
+
+		let run = self.buckets[0].pop().unwrap();
+		self.length -= run.1.len();
+		Some(run)
+	}
+
+	pub fn clear(&mut self) {
+		for bucket in &mut self.buckets { bucket.clear(); }
+		self.length = 0;
+	}
+}
+
+impl<V> Default for RleRadixHeap<V> {
+	fn default() -> RleRadixHeap<V> { RleRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_rle_heap() {
+		let mut rng = rand::thread_rng();
+		let mut heap = RleRadixHeap::new();
+		let mut model: Vec<u32> = Vec::new();
+
+		for _ in 0..5000 {
+			let key = rng.gen_range(0, 20) * 1000;
+			heap.push(key, key).unwrap();
+			model.push(key);
+		}
+
+		assert!(heap.runs() <= 20);
+		model.sort_unstable();
+
+		for (i, &key) in model.iter().enumerate() {
+			if i % 3 == 0 { assert_eq!(heap.peek(), Some((key, &key))); }
+			assert_eq!(heap.pop(), Some((key, key)));
+		}
+
+		assert!(heap.is_empty() && heap.runs() == 0);
+	}
+
+	#[test]
+	fn test_pop_ties() {
+		let mut heap = RleRadixHeap::new();
+		for (key, val) in [(4, 'a'), (2, 'b'), (4, 'c'), (9, 'd'), (2, 'e'), (4, 'f')] { heap.push(key, val).unwrap(); }
+		heap.extend_run(9, vec!['g', 'h']).unwrap();
+
+		assert_eq!(heap.pop_ties(), Some((2, vec!['b', 'e'])));
+		assert_eq!(heap.push(1, 'x'), Err("key too small"));
+		assert_eq!(heap.pop(), Some((4, 'f')));
+		assert_eq!(heap.pop_ties(), Some((4, vec!['a', 'c'])));
+		assert_eq!(heap.len(), 3);
+		assert_eq!(heap.pop_ties(), Some((9, vec!['d', 'g', 'h'])));
+		assert_eq!(heap.pop_ties(), None);
+	}
+}
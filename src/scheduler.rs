@@ -0,0 +1,295 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: scheduler.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashMap;
+use crate::clock::{Clock, ManualClock};
+use crate::timer::{Period, Repeat};
+use crate::RadixHeap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobHandle(u64);
+
+// earliest-deadline-first scheduler; a job whose deadline lies before the
+// deadline of the job popped last is treated as due at that deadline, as
+// the radix heap cannot go back below it
+#[derive(Clone, Debug)]
+pub struct Scheduler<J, C: Clock = ManualClock> {
+	// every heap entry carries a ticket, entries whose ticket no longer
+	// matches the one of their job are stale and skipped
+	heap: RadixHeap<(JobHandle, u64)>,
+	jobs: HashMap<JobHandle, (u32, u64, J)>,
+	periods: HashMap<JobHandle, Period<J>>,
+	next: u64,
+	bound: u32,
+	clock: C
+}
+
+impl<J> Scheduler<J> {
+	pub fn new() -> Scheduler<J> { Scheduler::with_clock(ManualClock::new()) }
+}
+
+impl<J, C: Clock> Scheduler<J, C> {
+	pub fn with_clock(clock: C) -> Scheduler<J, C> {
+		Scheduler { heap: RadixHeap::new(), jobs: HashMap::new(), periods: HashMap::new(), next: 0, bound: 0, clock }
+	}
+
+	pub fn clock(&self) -> &C { &self.clock }
+
+	pub fn len(&self) -> usize { self.jobs.len() }
+
+	pub fn is_empty(&self) -> bool { self.jobs.is_empty() }
+
+	pub fn contains(&self, handle: JobHandle) -> bool { self.jobs.contains_key(&handle) }
+
+	pub fn deadline(&self, handle: JobHandle) -> Option<u32> {
+		self.jobs.get(&handle).map(|&(d, _, _)| d)
+	}
+
+	pub fn submit(&mut self, deadline: u32, job: J) -> JobHandle {
+		let handle = JobHandle(self.ticket());
+		let ticket = self.enqueue(deadline, handle);
+
+		self.jobs.insert(handle, (deadline, ticket, job));
+		handle
+	}
+
+	// moves a job to a new deadline; returns "false" for unknown handles
+	pub fn reschedule(&mut self, handle: JobHandle, deadline: u32) -> bool {
+		if !self.jobs.contains_key(&handle) { return false; }
+
+		let ticket = self.enqueue(deadline, handle);
+		let job = self.jobs.get_mut(&handle).unwrap();
+		job.0 = deadline;
+		job.1 = ticket;
+
+		self.compact();
+		true
+	}
+
+	pub fn cancel(&mut self, handle: JobHandle) -> Option<J> {
+		self.periods.remove(&handle);
+		let job = self.jobs.remove(&handle).map(|(_, _, j)| j);
+		if job.is_some() { self.compact(); }
+		job
+	}
+
+	pub fn peek_deadline(&mut self) -> Option<u32> {
+		self.skip_stale();
+		self.heap.peek().map(|(_, (handle, _))| self.jobs[&handle].0)
+	}
+
+	// removes and returns the most urgent job
+	pub fn pop(&mut self) -> Option<(JobHandle, u32, J)> {
+		self.skip_stale();
+
+		let (key, (handle, _)) = self.heap.pop()?;
+		self.bound = key;
+		Some(self.take(handle, key))
+	}
+
+	// removes all jobs due at or before "now" at once, ordered by deadline;
+	// buckets due as a whole are drained without popping them item by item.
+	// The heap orders overdue jobs by the deadline they were clamped to, so
+	// the batch is sorted once more by the deadlines of the jobs.
+	pub fn poll_expired(&mut self, now: u32) -> Vec<(JobHandle, u32, J)> {
+		let due = self.heap.pop_range(..=now);
+		if let Some(&(key, _)) = due.last() { self.bound = self.bound.max(key); }
+
+		let mut expired: Vec<(JobHandle, u32, J)> = due.into_iter().filter_map(|(_, (handle, ticket))| {
+			if self.jobs.get(&handle).is_none_or(|&(_, t, _)| t != ticket) { return None; }
+			Some(self.take(handle, now))
+		}).collect();
+
+		expired.sort_by_key(|&(_, deadline, _)| deadline);
+		expired
+	}
+
+	// removes all jobs due at the current time of the clock
+	pub fn tick(&mut self) -> Vec<(JobHandle, u32, J)> {
+		let now = self.clock.now();
+		self.poll_expired(now)
+	}
+
+	pub fn clear(&mut self) {
+		self.heap = RadixHeap::new();
+		self.jobs.clear();
+		self.periods.clear();
+		self.bound = 0;
+	}
+
+	fn ticket(&mut self) -> u64 {
+		self.next += 1;
+		self.next
+	}
+
+	fn enqueue(&mut self, deadline: u32, handle: JobHandle) -> u64 {
+		let ticket = self.ticket();
+		self.heap.push(deadline.max(self.bound), (handle, ticket)).unwrap();
+		ticket
+	}
+
+	// removes a live job; a repeating job run at "now" is submitted again
+	// and handed out as a copy instead
+	fn take(&mut self, handle: JobHandle, now: u32) -> (JobHandle, u32, J) {
+		let deadline = self.jobs[&handle].0;
+
+		if let Some(period) = self.periods.get(&handle) {
+			let (next, copy) = (period.next(deadline, now), period.copy);
+
+			if let Some(next) = next {
+				let ticket = self.enqueue(next, handle);
+				let job = self.jobs.get_mut(&handle).unwrap();
+				(job.0, job.1) = (next, ticket);
+				return (handle, deadline, copy(&job.2));
+			}
+
+			self.periods.remove(&handle);
+		}
+
+		let (_, _, job) = self.jobs.remove(&handle).unwrap();
+		(handle, deadline, job)
+	}
+
+	fn skip_stale(&mut self) {
+		while let Some((key, (handle, ticket))) = self.heap.peek() {
+			if self.jobs.get(&handle).is_some_and(|&(_, t, _)| t == ticket) { break; }
+
+			self.heap.pop();
+			self.bound = key;
+		}
+	}
+
+	// rebuilds the heap once stale entries outnumber the live ones
+	fn compact(&mut self) {
+		if self.heap.len() <= 2 * self.jobs.len() + 32 { return; }
+
+		self.heap = RadixHeap::new();
+
+		for (&handle, &(deadline, ticket, _)) in &self.jobs {
+			self.heap.push(deadline.max(self.bound), (handle, ticket)).unwrap();
+		}
+	}
+}
+
+impl<J: Clone, C: Clock> Scheduler<J, C> {
+	// runs every "interval" ticks, first "interval" ticks after the current
+	// time of the clock or the deadline of the job popped last, whichever
+	// is later, until the job is cancelled. "pop()" runs it once per
+	// period, "poll_expired()" and "tick()" once per call, the next deadline
+	// depending on "repeat"
+	pub fn schedule_repeating(&mut self, interval: u32, repeat: Repeat, job: J) -> JobHandle {
+		let period = Period::new(interval, repeat);
+		let handle = self.submit(self.clock.now().max(self.bound).saturating_add(interval), job);
+		self.periods.insert(handle, period);
+		handle
+	}
+}
+
+impl<J> Default for Scheduler<J> {
+	fn default() -> Scheduler<J> { Scheduler::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_scheduler() {
+		let mut scheduler = Scheduler::new();
+		let backup = scheduler.submit(500, "backup");
+		let render = scheduler.submit(40, "render");
+		let report = scheduler.submit(90, "report");
+
+		assert!(scheduler.reschedule(backup, 20));
+		assert_eq!(scheduler.cancel(report), Some("report"));
+		assert_eq!(scheduler.peek_deadline(), Some(20));
+
+		assert_eq!(scheduler.pop(), Some((backup, 20, "backup")));
+		assert_eq!(scheduler.pop(), Some((render, 40, "render")));
+
+		// overdue jobs still keep their own deadline
+		let late = scheduler.submit(10, "late");
+		assert_eq!(scheduler.pop(), Some((late, 10, "late")));
+		assert_eq!(scheduler.pop(), None);
+		assert!(!scheduler.reschedule(late, 50));
+	}
+
+	#[test]
+	fn test_poll_expired() {
+		let mut scheduler = Scheduler::new();
+		let jobs: Vec<JobHandle> = (0..100).map(|i| scheduler.submit(i * 10, i)).collect();
+		scheduler.reschedule(jobs[3], 2000);
+		scheduler.cancel(jobs[5]);
+
+		let due = scheduler.poll_expired(95);
+		assert_eq!(due.iter().map(|&(_, _, j)| j).collect::<Vec<u32>>(), vec![0, 1, 2, 4, 6, 7, 8, 9]);
+		assert_eq!(due[3], (jobs[4], 40, 4));
+		assert_eq!(scheduler.len(), 91);
+
+		assert!(scheduler.poll_expired(95).is_empty());
+		assert_eq!(scheduler.pop(), Some((jobs[10], 100, 10)));
+
+		// overdue jobs share the key of the job popped last
+		let (late, later) = (scheduler.submit(60, 1000), scheduler.submit(30, 1001));
+		let due = scheduler.poll_expired(100);
+		assert_eq!(due.iter().map(|&(h, d, _)| (h, d)).collect::<Vec<_>>(), vec![(later, 30), (late, 60)]);
+	}
+
+	#[test]
+	fn test_repeating() {
+		let mut scheduler = Scheduler::new();
+		let rate = scheduler.schedule_repeating(10, Repeat::FixedRate, "rate");
+		let delay = scheduler.schedule_repeating(10, Repeat::FixedDelay, "delay");
+
+		assert_eq!(scheduler.pop(), Some((rate, 10, "rate")));
+		assert_eq!(scheduler.pop(), Some((delay, 10, "delay")));
+		assert_eq!(scheduler.deadline(rate), Some(20));
+
+		// polled at 35: the fixed rate job runs once for 20 and 30 and
+		// moves on to 40, the fixed delay one moves on to 45
+		assert_eq!(scheduler.poll_expired(35).len(), 2);
+		assert_eq!((scheduler.deadline(rate), scheduler.deadline(delay)), (Some(40), Some(45)));
+
+		assert_eq!(scheduler.cancel(delay), Some("delay"));
+		let end = scheduler.submit(u32::MAX - 5, "end");
+
+		// no deadline is left after u32::MAX, so the job runs a last time
+		assert_eq!(scheduler.poll_expired(u32::MAX).into_iter().map(|(h, d, _)| (h, d)).collect::<Vec<_>>(),
+		           vec![(rate, 40), (end, u32::MAX - 5)]);
+		assert!(scheduler.is_empty());
+	}
+
+	#[test]
+	fn test_clock() {
+		let clock = ManualClock::new();
+		let mut scheduler = Scheduler::with_clock(clock.clone());
+		let (a, b) = (scheduler.submit(30, "a"), scheduler.submit(60, "b"));
+
+		clock.set(45);
+		assert_eq!(scheduler.tick(), vec![(a, 30, "a")]);
+		assert!(scheduler.tick().is_empty());
+		clock.advance(15);
+		assert_eq!(scheduler.tick(), vec![(b, 60, "b")]);
+	}
+}
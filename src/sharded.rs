@@ -0,0 +1,129 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: sharded.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use crate::RadixHeap;
+use crate::sync::thread_index;
+
+// Every thread pushes into its own radix heap shard, so the bucketing work
+// is done by the producers in parallel. Popping locks all shards and takes
+// the smallest of their tops, which keeps the ordering exact: the global
+// bound only rises while no producer holds a shard, and no shard's own
+// bound ever exceeds it.
+pub struct ShardedRadixHeap<V: Clone + Debug + Ord> {
+	shards: Vec<Mutex<RadixHeap<V>>>,
+	bound: AtomicU32,
+	length: AtomicUsize
+}
+
+impl<V: Clone + Debug + Ord> ShardedRadixHeap<V> {
+	pub fn new(shards: usize) -> ShardedRadixHeap<V> {
+		ShardedRadixHeap {
+			shards: (0..shards.max(1)).map(|_| Mutex::new(RadixHeap::new())).collect(),
+			bound: AtomicU32::new(0),
+			length: AtomicUsize::new(0)
+		}
+	}
+
+	pub fn shards(&self) -> usize { self.shards.len() }
+
+	pub fn len(&self) -> usize { self.length.load(Ordering::SeqCst) }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	// pushes into the shard of the calling thread
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> {
+		self.push_to(thread_index(self.shards.len()), key, val)
+	}
+
+	pub fn push_to(&self, shard: usize, key: u32, val: V) -> Result<(), &'static str> {
+		let mut shard = self.shards[shard % self.shards.len()].lock().unwrap();
+		if key < self.bound.load(Ordering::SeqCst) { return Err("key too small"); }
+
+		shard.push(key, val).map_err(|_| "key too small")?;
+		self.length.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	}
+
+	pub fn pop(&self) -> Option<(u32, V)> {
+		let mut shards: Vec<_> = self.shards.iter().map(|s| s.lock().unwrap()).collect();
+		let index = (0..shards.len())
+			.filter_map(|i| shards[i].peek_key().map(|k| (k, i)))
+			.min()?.1;
+		let top = shards[index].pop();
+
+		if let Some((key, _)) = top {
+			self.bound.store(key, Ordering::SeqCst);
+			self.length.fetch_sub(1, Ordering::SeqCst);
+		}
+
+		top
+	}
+
+	pub fn peek(&self) -> Option<(u32, V)> {
+		let shards: Vec<_> = self.shards.iter().map(|s| s.lock().unwrap()).collect();
+		shards.iter().filter_map(|s| s.peek()).min_by_key(|&(k, _)| k)
+	}
+}
+
+// shareable between threads as long as the values can be sent
+#[allow(dead_code)]
+const _: () = {
+	fn send_sync<T: Send + Sync>() {}
+	fn is_send_sync<V: Clone + Debug + Ord + Send>() { send_sync::<ShardedRadixHeap<V>>(); }
+};
+
+impl<V: Clone + Debug + Ord> Default for ShardedRadixHeap<V> {
+	fn default() -> ShardedRadixHeap<V> { ShardedRadixHeap::new(8) }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::Arc;
+	use std::thread;
+
+	#[test]
+	fn test_sharded_heap() {
+		let heap: Arc<ShardedRadixHeap<u32>> = Arc::new(ShardedRadixHeap::new(4));
+
+		let producers: Vec<_> = (0..4u32).map(|p| {
+			let heap = Arc::clone(&heap);
+			thread::spawn(move || {
+				for i in 0..250u32 { heap.push(i * 4 + p, p).unwrap(); }
+			})
+		}).collect();
+
+		for producer in producers { producer.join().unwrap(); }
+		assert_eq!(heap.len(), 1000);
+		assert_eq!(heap.peek().map(|(k, _)| k), Some(0));
+
+		let keys: Vec<u32> = (0..1000).map(|_| heap.pop().unwrap().0).collect();
+		assert_eq!(keys, (0..1000).collect::<Vec<u32>>());
+		assert_eq!(heap.pop(), None);
+		assert_eq!(heap.push_to(2, 998, 0), Err("key too small"));
+	}
+}
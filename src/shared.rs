@@ -0,0 +1,100 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: shared.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::sync::{Mutex, OnceLock};
+use crate::RadixHeap;
+
+// heap meant to live in a "static", e.g. as the process-wide queue of a
+// scheduler or behind an FFI boundary; it is created on first use
+//
+//     static QUEUE: SharedRadixHeap<u64> = SharedRadixHeap::new();
+//     QUEUE.push(10, 42).unwrap();
+pub struct SharedRadixHeap<V: Clone + Debug + Ord> {
+	heap: OnceLock<Mutex<RadixHeap<V>>>,
+	init: fn() -> RadixHeap<V>
+}
+
+impl<V: Clone + Debug + Ord> SharedRadixHeap<V> {
+	pub const fn new() -> SharedRadixHeap<V> { SharedRadixHeap::with_init(RadixHeap::new) }
+
+	// "init" builds the heap on first use, e.g. with some capacity reserved
+	pub const fn with_init(init: fn() -> RadixHeap<V>) -> SharedRadixHeap<V> {
+		SharedRadixHeap { heap: OnceLock::new(), init }
+	}
+
+	// runs "f" with the heap locked, for several operations in one go
+	pub fn with<R, F: FnOnce(&mut RadixHeap<V>) -> R>(&self, f: F) -> R {
+		f(&mut self.heap.get_or_init(|| Mutex::new((self.init)())).lock().unwrap())
+	}
+
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> { self.with(|heap| heap.push(key, val)) }
+
+	pub fn pop(&self) -> Option<(u32, V)> { self.with(|heap| heap.pop()) }
+
+	pub fn peek(&self) -> Option<(u32, V)> { self.with(|heap| heap.peek()) }
+
+	pub fn len(&self) -> usize { self.with(|heap| heap.len()) }
+
+	pub fn is_empty(&self) -> bool { self.with(|heap| heap.is_empty()) }
+
+	pub fn bound(&self) -> u32 { self.with(|heap| heap.bound()) }
+
+	pub fn clear(&self) { self.with(|heap| heap.clear()) }
+}
+
+impl<V: Clone + Debug + Ord> Default for SharedRadixHeap<V> {
+	fn default() -> SharedRadixHeap<V> { SharedRadixHeap::new() }
+}
+
+// a "static" requires "Sync", which holds whenever the values can be sent
+#[allow(dead_code)]
+const _: () = {
+	fn send_sync<T: Send + Sync>() {}
+	fn is_send_sync<V: Clone + Debug + Ord + Send>() { send_sync::<SharedRadixHeap<V>>(); }
+};
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::thread;
+
+	static QUEUE: SharedRadixHeap<u32> = SharedRadixHeap::with_init(|| RadixHeap::with_capacity(330));
+
+	#[test]
+	fn test_shared_heap() {
+		let producers: Vec<_> = (0..4u32).map(|p| {
+			thread::spawn(move || for i in 0..100 { QUEUE.push(p * 1000 + i, p).unwrap(); })
+		}).collect();
+
+		for producer in producers { producer.join().unwrap(); }
+		assert_eq!(QUEUE.len(), 400);
+		assert!(QUEUE.with(|heap| heap.capacity()) >= 330);
+
+		let keys: Vec<u32> = std::iter::from_fn(|| QUEUE.pop()).map(|(k, _)| k).collect();
+		assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+		assert_eq!(QUEUE.push(0, 0), Err("key too small"));
+		assert!(QUEUE.is_empty());
+	}
+}
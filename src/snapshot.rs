@@ -0,0 +1,273 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: snapshot.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::io::{self, ErrorKind, Read, Write};
+use crate::external::Spill;
+use crate::RadixHeap;
+
+const MAGIC: &[u8; 4] = b"RXH1";
+const DRAIN_MAGIC: &[u8; 4] = b"RXD1";
+
+const PUSH: u8 = 0;
+const POP: u8 = 1;
+const CLEAR: u8 = 2;
+
+fn invalid(message: &'static str) -> io::Error { io::Error::new(ErrorKind::InvalidData, message) }
+
+// writes the complete state of a heap, including the order of the items
+// inside every bucket, so restoring it yields the very same pop order
+pub fn snapshot<V, W>(heap: &RadixHeap<V>, out: &mut W) -> io::Result<()>
+	where V: Clone + Debug + Ord + Spill, W: Write {
+	let (toplast, buckets) = heap.raw_buckets();
+	out.write_all(MAGIC)?;
+	toplast.spill(out)?;
+
+	for items in buckets {
+		items.len().spill(out)?;
+		for (key, val) in items {
+			key.spill(out)?;
+			val.spill(out)?;
+		}
+	}
+
+	Ok(())
+}
+
+// rebuilds a heap from a snapshot while reading it: every record goes
+// straight into its bucket, so the serialized form is never held in memory
+// next to the heap
+pub fn load<V, R>(mut snapshot: R) -> io::Result<RadixHeap<V>>
+	where V: Clone + Debug + Ord + Spill, R: Read {
+	let mut magic = [0u8; 4];
+	snapshot.read_exact(&mut magic)?;
+	if &magic != MAGIC { return Err(invalid("not a radix heap snapshot")); }
+
+	let mut heap = RadixHeap::with_bound(u32::restore(&mut snapshot)?);
+	for index in 0..33 {
+		for _ in 0..usize::restore(&mut snapshot)? {
+			let (key, val) = (u32::restore(&mut snapshot)?, V::restore(&mut snapshot)?);
+			heap.place_raw(index, key, val).map_err(invalid)?;
+		}
+	}
+
+	Ok(heap)
+}
+
+// rebuilds a heap from a snapshot and replays the operations of a log
+// written afterwards; a record torn by a crash ends the replay
+pub fn restore<V, S, L>(snapshot: S, mut log: L) -> io::Result<RadixHeap<V>>
+	where V: Clone + Debug + Ord + Spill, S: Read, L: Read {
+	let mut heap = load(snapshot)?;
+
+	loop {
+		let mut tag = [0u8; 1];
+		if log.read(&mut tag)? == 0 { break; }
+
+		match tag[0] {
+			PUSH => {
+				let record = u32::restore(&mut log).and_then(|k| Ok((k, V::restore(&mut log)?)));
+				match record {
+					Ok((key, val)) => heap.push(key, val).map_err(|_| invalid("logged push rejected"))?,
+					Err(ref e) if e.kind() == ErrorKind::UnexpectedEof => break,
+					Err(e) => return Err(e)
+				}
+			},
+			POP => { heap.pop().ok_or_else(|| invalid("logged pop from empty heap"))?; },
+			CLEAR => heap.clear(),
+			_ => return Err(invalid("unknown log record"))
+		}
+	}
+
+	Ok(heap)
+}
+
+// heap appending every successful operation to a write-ahead log; the log
+// is written before the operation is applied
+pub struct LoggedRadixHeap<V: Clone + Debug + Ord + Spill, W: Write> {
+	heap: RadixHeap<V>,
+	log: W
+}
+
+impl<V: Clone + Debug + Ord + Spill, W: Write> LoggedRadixHeap<V, W> {
+	// "log" has to continue where the last snapshot of "heap" was taken
+	pub fn new(heap: RadixHeap<V>, log: W) -> LoggedRadixHeap<V, W> { LoggedRadixHeap { heap, log } }
+
+	pub fn heap(&self) -> &RadixHeap<V> { &self.heap }
+
+	pub fn len(&self) -> usize { self.heap.len() }
+
+	pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+	pub fn peek(&self) -> Option<(u32, V)> { self.heap.peek() }
+
+	pub fn push(&mut self, key: u32, val: V) -> io::Result<()> {
+		if key < self.heap.bound() { return Err(io::Error::new(ErrorKind::InvalidInput, "key too small")); }
+
+		self.log.write_all(&[PUSH])?;
+		key.spill(&mut self.log)?;
+		val.spill(&mut self.log)?;
+		self.heap.push(key, val).unwrap();
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> io::Result<Option<(u32, V)>> {
+		if self.heap.is_empty() { return Ok(None); }
+
+		self.log.write_all(&[POP])?;
+		Ok(self.heap.pop())
+	}
+
+	pub fn clear(&mut self) -> io::Result<()> {
+		self.log.write_all(&[CLEAR])?;
+		self.heap.clear();
+		Ok(())
+	}
+
+	pub fn flush(&mut self) -> io::Result<()> { self.log.flush() }
+
+	pub fn into_parts(self) -> (RadixHeap<V>, W) { (self.heap, self.log) }
+}
+
+// sorted drain of a heap that can be checkpointed at any point and resumed
+// from that checkpoint later, e.g. by another process, handing out the
+// remaining items in the very same order
+pub struct ResumableDrain<V: Clone + Debug + Ord + Spill> {
+	heap: RadixHeap<V>,
+	drained: u64
+}
+
+impl<V: Clone + Debug + Ord + Spill> ResumableDrain<V> {
+	pub fn new(heap: RadixHeap<V>) -> ResumableDrain<V> { ResumableDrain { heap, drained: 0 } }
+
+	// number of items handed out since the drain started, across resumes
+	pub fn drained(&self) -> u64 { self.drained }
+
+	pub fn remaining(&self) -> usize { self.heap.len() }
+
+	// key of the item handed out last
+	pub fn bound(&self) -> u32 { self.heap.bound() }
+
+	pub fn checkpoint<W: Write>(&self, out: &mut W) -> io::Result<()> {
+		out.write_all(DRAIN_MAGIC)?;
+		self.drained.spill(out)?;
+		snapshot(&self.heap, out)
+	}
+
+	pub fn resume<R: Read>(mut input: R) -> io::Result<ResumableDrain<V>> {
+		let mut magic = [0u8; 4];
+		input.read_exact(&mut magic)?;
+		if &magic != DRAIN_MAGIC { return Err(invalid("not a drain checkpoint")); }
+
+		let drained = u64::restore(&mut input)?;
+		Ok(ResumableDrain { heap: load(input)?, drained })
+	}
+
+	pub fn into_heap(self) -> RadixHeap<V> { self.heap }
+}
+
+impl<V: Clone + Debug + Ord + Spill> Iterator for ResumableDrain<V> {
+	type Item = (u32, V);
+
+	fn next(&mut self) -> Option<(u32, V)> {
+		let item = self.heap.pop()?;
+		self.drained += 1;
+		Some(item)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) { (self.heap.len(), Some(self.heap.len())) }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_snapshot_replay() {
+		let mut heap = RadixHeap::new();
+		for key in &[4u32, 9, 4, 300, 17] { heap.push(*key, *key as u64).unwrap(); }
+		heap.pop();
+
+		let mut image = Vec::new();
+		snapshot(&heap, &mut image).unwrap();
+
+		let mut logged = LoggedRadixHeap::new(heap, Vec::new());
+		logged.push(20, 20).unwrap();
+		assert!(logged.push(1, 1).is_err());
+		assert_eq!(logged.pop().unwrap(), Some((4, 4)));
+		logged.push(6, 6).unwrap();
+
+		let (heap, mut log) = logged.into_parts();
+		let restored: RadixHeap<u64> = restore(&image[..], &log[..]).unwrap();
+		assert_eq!(restored.raw_buckets(), heap.raw_buckets());
+		assert_eq!(restored.len(), heap.len());
+
+		// a push torn by a crash is dropped
+		log.extend_from_slice(&[PUSH, 99]);
+		let restored: RadixHeap<u64> = restore(&image[..], &log[..]).unwrap();
+		assert_eq!(restored.keys(), vec![6, 9, 17, 20, 300]);
+
+		assert!(restore::<u64, _, _>(&b"nope"[..], &log[..]).is_err());
+	}
+
+	#[test]
+	fn test_load() {
+		let mut heap = RadixHeap::new();
+		for key in [12u32, 1 << 30, 12, 77, 5, 90000] { heap.push(key, key as u64).unwrap(); }
+		heap.pop();
+
+		let mut image = Vec::new();
+		snapshot(&heap, &mut image).unwrap();
+		let loaded: RadixHeap<u64> = load(std::io::BufReader::with_capacity(8, &image[..])).unwrap();
+		assert_eq!(loaded.raw_buckets(), heap.raw_buckets());
+		assert_eq!((loaded.len(), loaded.bound()), (5, 5));
+
+		// another bound puts the items into the wrong buckets
+		image[4] = 0xff;
+		assert!(load::<u64, _>(&image[..]).is_err());
+		assert!(load::<u64, _>(&image[..image.len() - 1]).is_err());
+	}
+
+	#[test]
+	fn test_resumable_drain() {
+		let mut heap = RadixHeap::new();
+		for key in [70u32, 3, 3, 900, 41, 3, 12, 5000, 41] { heap.push(key, key as u64 * 2).unwrap(); }
+
+		let mut drain = ResumableDrain::new(heap);
+		let mut head: Vec<(u32, u64)> = drain.by_ref().take(4).collect();
+
+		let mut image = Vec::new();
+		drain.checkpoint(&mut image).unwrap();
+		let tail: Vec<(u32, u64)> = drain.collect();
+
+		let mut resumed: ResumableDrain<u64> = ResumableDrain::resume(&image[..]).unwrap();
+		assert_eq!((resumed.drained(), resumed.remaining(), resumed.bound()), (4, 5, 12));
+		assert_eq!(resumed.by_ref().collect::<Vec<_>>(), tail);
+		assert_eq!(resumed.drained(), 9);
+
+		head.extend(tail);
+		assert_eq!(head.iter().map(|&(k, _)| k).collect::<Vec<u32>>(), vec![3, 3, 3, 12, 41, 41, 70, 900, 5000]);
+		assert!(ResumableDrain::<u64>::resume(&image[4..]).is_err());
+	}
+}
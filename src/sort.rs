@@ -0,0 +1,83 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: sort.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use crate::RadixHeap;
+
+// both sorts are unstable: elements with equal keys may be reordered
+
+pub fn radix_heap_sort<V>(slice: &mut [(u32, V)]) {
+	radix_heap_sort_by_key(slice, |t| t.0)
+}
+
+pub fn radix_heap_sort_by_key<T, F: FnMut(&T) -> u32>(slice: &mut [T], mut f: F) {
+	let mut heap: RadixHeap<usize> = RadixHeap::new();
+	let mut dest: Vec<usize> = vec![0; slice.len()];
+
+	// only indices go through the heap, the elements are moved afterwards
+	for (index, t) in slice.iter().enumerate() { heap.push(f(t), index).unwrap(); }
+
+	let mut position = 0;
+	while let Some((_, index)) = heap.pop() {
+		dest[index] = position;
+		position += 1;
+	}
+
+	// every swap moves one element to its final position
+	for i in 0..slice.len() {
+		while dest[i] != i {
+			let d = dest[i];
+			slice.swap(i, d);
+			dest.swap(i, d);
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_sort() {
+		let mut rng = rand::thread_rng();
+		let mut tuples: Vec<(u32, u32)> = (0..500).map(|i| (rng.gen(), i)).collect();
+		let mut expected = tuples.clone();
+
+		radix_heap_sort(&mut tuples);
+		expected.sort_unstable_by_key(|t| t.0);
+
+		assert_eq!(tuples.iter().map(|t| t.0).collect::<Vec<u32>>(),
+		           expected.iter().map(|t| t.0).collect::<Vec<u32>>());
+	}
+
+	#[test]
+	fn test_sort_by_key() {
+		let mut words = vec!["radix", "a", "heap", "is", "fast"];
+		radix_heap_sort_by_key(&mut words, |w| w.len() as u32);
+
+		assert_eq!(words[0], "a");
+		assert_eq!(words[1], "is");
+		assert_eq!(words[4], "radix");
+	}
+}
@@ -0,0 +1,134 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: sorted.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::mem;
+
+// radix heap whose buckets are kept sorted by descending key, so the
+// smallest item of a bucket is always its last one: pops take it off in
+// constant time and redistributing a bucket appends to the lower buckets in
+// order without searching for the new bound. Pushes pay for a binary search
+// and shifting the bucket instead, which suits pop-heavy workloads. Equal
+// keys are popped in the order they were pushed.
+#[derive(Clone, Debug)]
+pub struct SortedRadixHeap<V> {
+	buckets: Vec<Vec<(u32, V)>>,
+	toplast: u32,
+	length: usize
+}
+
+impl<V> SortedRadixHeap<V> {
+	pub fn new() -> SortedRadixHeap<V> {
+		SortedRadixHeap { buckets: (0..33).map(|_| Vec::new()).collect(), toplast: 0, length: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	pub fn bound(&self) -> u32 { self.toplast }
+
+	fn bucket_index(&self, key: u32) -> usize {
+		if key == self.toplast { 0 } else { 32 - (key ^ self.toplast).leading_zeros() as usize }
+	}
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.toplast { return Err("key too small"); }
+
+		// new items go in front of those with equal keys
+		let index = self.bucket_index(key);
+		let bucket = &mut self.buckets[index];
+		let position = bucket.partition_point(|&(k, _)| k > key);
+		bucket.insert(position, (key, val));
+		self.length += 1;
+		Ok(())
+	}
+
+	pub fn peek(&self) -> Option<(u32, &V)> {
+		let bucket = self.buckets.iter().find(|b| !b.is_empty())?;
+		bucket.last().map(|(k, v)| (*k, v))
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let index = self.buckets.iter().position(|b| !b.is_empty())?;
+		let top = self.buckets[index].pop();
+		self.length -= 1;
+
+		let Some((key, _)) = top else { return top };
+		self.toplast = key;
+
+		// all lower buckets are empty, so appending the remaining items in
+		// their order keeps each of them sorted
+		if index > 0 {
+			let mut items = mem::take(&mut self.buckets[index]);
+			for (key, val) in items.drain(..) {
+				let to = self.bucket_index(key);
+				self.buckets[to].push((key, val));
+			}
+			self.buckets[index] = items;
+		}
+
+		top
+	}
+
+	pub fn clear(&mut self) {
+		for bucket in &mut self.buckets { bucket.clear(); }
+		self.length = 0;
+	}
+}
+
+impl<V> Default for SortedRadixHeap<V> {
+	fn default() -> SortedRadixHeap<V> { SortedRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_sorted_heap() {
+		let mut rng = rand::thread_rng();
+		let mut heap = SortedRadixHeap::new();
+		let mut model: Vec<(u32, usize)> = Vec::new();
+
+		for i in 0..3000 {
+			let key = if i % 2 == 0 { rng.gen() } else { rng.gen_range(0, 100) };
+			heap.push(key, i).unwrap();
+			model.push((key, i));
+		}
+
+		// stable sort, so equal keys stay in push order
+		model.sort_by_key(|&(k, _)| k);
+
+		for (i, &(key, val)) in model.iter().enumerate() {
+			if i % 5 == 0 { assert_eq!(heap.peek(), Some((key, &val))); }
+			assert_eq!(heap.pop(), Some((key, val)));
+			assert!(heap.buckets.iter().all(|b| b.windows(2).all(|w| w[0].0 >= w[1].0)));
+		}
+
+		assert!(heap.is_empty() && heap.pop().is_none());
+		assert_eq!(heap.bound(), model[2999].0);
+		assert_eq!(heap.push(heap.bound() - 1, 0), Err("key too small"));
+	}
+}
@@ -0,0 +1,139 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: split.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use crate::RadixHeap;
+
+// radix heap for skewed key distributions: once a bucket holds
+// "threshold" items, further items falling into it are kept in a sorted
+// map split by key instead, so popping from that bucket never has to
+// redistribute more than "threshold" items at once. Pushes into the map
+// cost a logarithmic insertion instead.
+#[derive(Clone, Debug)]
+pub struct SplitRadixHeap<V: Clone + Debug + Ord> {
+	heap: RadixHeap<V>,
+	overflow: BTreeMap<u32, Vec<V>>,
+	overflowed: usize,
+	threshold: usize,
+	// largest key popped so far, which may lie above the bound of "heap"
+	bound: u32
+}
+
+impl<V: Clone + Debug + Ord> SplitRadixHeap<V> {
+	pub fn new(threshold: usize) -> SplitRadixHeap<V> {
+		assert!(threshold > 0, "split threshold must not be zero");
+		SplitRadixHeap { heap: RadixHeap::new(), overflow: BTreeMap::new(), overflowed: 0, threshold, bound: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.heap.len() + self.overflowed }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	pub fn bound(&self) -> u32 { self.bound }
+
+	// number of items kept outside of the buckets
+	pub fn overflowed(&self) -> usize { self.overflowed }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.bound { return Err("key too small"); }
+
+		// bucket 0 is popped from without redistribution
+		let bucket = self.heap.bucket_for_key(key).unwrap();
+		if bucket > 0 && self.heap.bucket_view(bucket).unwrap().len() >= self.threshold {
+			self.overflow.entry(key).or_default().push(val);
+			self.overflowed += 1;
+			Ok(())
+		} else { self.heap.push(key, val) }
+	}
+
+	pub fn peek(&self) -> Option<(u32, &V)> {
+		let first = self.overflow.iter().next().map(|(&k, vals)| (k, vals.last().unwrap()));
+
+		match (self.heap.peek_key(), first) {
+			(Some(key), Some((k, _))) if key <= k => Some((key, self.heap.peek_value()?)),
+			(Some(key), None) => Some((key, self.heap.peek_value()?)),
+			(_, first) => first
+		}
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let first = self.overflow.keys().next().copied();
+
+		let top = match (self.heap.peek_key(), first) {
+			(Some(key), Some(k)) if key <= k => self.heap.pop(),
+			(Some(_), None) => self.heap.pop(),
+			(_, Some(k)) => {
+				let mut entry = self.overflow.first_entry().unwrap();
+				let val = entry.get_mut().pop().unwrap();
+				if entry.get().is_empty() { entry.remove(); }
+
+				self.overflowed -= 1;
+				Some((k, val))
+			},
+			(None, None) => None
+		};
+
+		if let Some((key, _)) = top { self.bound = key; }
+		top
+	}
+
+	pub fn clear(&mut self) {
+		self.heap.clear();
+		self.overflow.clear();
+		self.overflowed = 0;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	#[test]
+	fn test_split_heap() {
+		let mut rng = rand::thread_rng();
+		let mut heap = SplitRadixHeap::new(64);
+		let mut model: Vec<u32> = Vec::new();
+
+		// most keys fall into the highest bucket
+		for _ in 0..5000 {
+			let key = if rng.gen_range(0, 10) == 0 { rng.gen_range(0, 1000) } else { rng.gen_range(1 << 31, u32::MAX) };
+			heap.push(key, key).unwrap();
+			model.push(key);
+		}
+
+		assert!(heap.overflowed() > 4000);
+		assert!(heap.heap.bucket_views().all(|v| v.len() <= 64));
+		model.sort_unstable();
+
+		for (i, &key) in model.iter().enumerate() {
+			if i % 7 == 0 { assert_eq!(heap.peek(), Some((key, &key))); }
+			assert_eq!(heap.pop(), Some((key, key)));
+			if key > 0 { assert_eq!(heap.push(key - 1, 0), Err("key too small")); }
+		}
+
+		assert!(heap.is_empty() && heap.pop().is_none());
+	}
+}
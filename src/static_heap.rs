@@ -0,0 +1,194 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: static_heap.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use core::fmt::Debug;
+
+const BUCKETS: usize = 33;
+
+// radix heap of at most "N" items that never allocates: the buckets are
+// consecutive segments of one array, "ends[b]" being the end of segment
+// "b". Inserting into a bucket moves one item of each later segment, so
+// every operation except restructuring touches at most 33 items.
+#[derive(Clone, Debug)]
+pub struct StaticRadixHeap<V, const N: usize> {
+	items: [Option<(u32, V)>; N],
+	ends: [usize; BUCKETS],
+	toplast: u32
+}
+
+impl<V: Clone + Debug + Ord, const N: usize> StaticRadixHeap<V, N> {
+	// usable in constant expressions, so a heap can be put into a "static"
+	pub const fn new() -> StaticRadixHeap<V, N> {
+		StaticRadixHeap { items: [const { None }; N], ends: [0; BUCKETS], toplast: 0 }
+	}
+
+	pub const fn len(&self) -> usize { self.ends[BUCKETS - 1] }
+
+	pub const fn is_empty(&self) -> bool { self.len() == 0 }
+
+	pub const fn is_full(&self) -> bool { self.len() == N }
+
+	pub const fn capacity(&self) -> usize { N }
+
+	pub const fn bound(&self) -> u32 { self.toplast }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.toplast { return Err("key too small"); }
+		if self.is_full() { return Err("heap is full"); }
+
+		// the free slot travels down to the end of the target segment
+		let bucket = self.bucket_index(key);
+		for b in (bucket + 1..BUCKETS).rev() {
+			let start = self.start(b);
+			if start < self.ends[b] { self.items.swap(start, self.ends[b]); }
+			self.ends[b] += 1;
+		}
+
+		self.items[self.ends[bucket]] = Some((key, val));
+		self.ends[bucket] += 1;
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		if self.is_empty() { return None; }
+		if self.ends[0] == 0 { self.restructure(); }
+
+		// the free slot travels up to the end of the array
+		self.ends[0] -= 1;
+		let top = self.items[self.ends[0]].take();
+
+		for b in 1..BUCKETS {
+			let free = self.start(b);
+			if free + 1 < self.ends[b] { self.items.swap(free, self.ends[b] - 1); }
+			self.ends[b] -= 1;
+		}
+
+		top
+	}
+
+	pub fn peek(&self) -> Option<(u32, &V)> {
+		let bucket = (0..BUCKETS).find(|&b| self.start(b) < self.ends[b])?;
+		let items = self.items[self.start(bucket)..self.ends[bucket]].iter().flatten();
+		items.min_by_key(|(k, _)| *k).map(|(k, v)| (*k, v))
+	}
+
+	pub fn clear(&mut self) {
+		let len = self.len();
+		for item in &mut self.items[..len] { *item = None; }
+		self.ends = [0; BUCKETS];
+	}
+
+	fn start(&self, bucket: usize) -> usize { if bucket == 0 { 0 } else { self.ends[bucket - 1] } }
+
+	fn bucket_index(&self, key: u32) -> usize {
+		if key == self.toplast { 0 } else { 32 - (key ^ self.toplast).leading_zeros() as usize }
+	}
+
+	// bucket 0 is empty: all lower segments of the first non-empty bucket
+	// are empty as well, so its items sit at the front of the array and
+	// just need to be sorted by their new buckets
+	fn restructure(&mut self) {
+		let bucket = (1..BUCKETS).find(|&b| self.ends[b] > 0).unwrap();
+		let end = self.ends[bucket];
+		let segment = &mut self.items[..end];
+
+		self.toplast = segment.iter().flatten().map(|(k, _)| *k).min().unwrap();
+		let toplast = self.toplast;
+		let index = |item: &Option<(u32, V)>| {
+			let key = item.as_ref().unwrap().0;
+			if key == toplast { 0 } else { 32 - (key ^ toplast).leading_zeros() as usize }
+		};
+
+		segment.sort_unstable_by_key(index);
+
+		let mut position = 0;
+		for b in 0..bucket {
+			while position < end && index(&self.items[position]) == b { position += 1; }
+			self.ends[b] = position;
+		}
+	}
+}
+
+impl<V: Clone + Debug + Ord, const N: usize> Default for StaticRadixHeap<V, N> {
+	fn default() -> StaticRadixHeap<V, N> { StaticRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::Mutex;
+
+	#[test]
+	fn test_static_heap() {
+		let mut heap: StaticRadixHeap<char, 4> = StaticRadixHeap::new();
+		heap.push(40, 'c').unwrap();
+		heap.push(7, 'a').unwrap();
+		heap.push(9, 'b').unwrap();
+		heap.push(7, 'x').unwrap();
+		assert_eq!(heap.push(50, 'd'), Err("heap is full"));
+
+		assert_eq!(heap.peek().map(|(k, _)| k), Some(7));
+		let (_, first) = heap.pop().unwrap();
+		let (_, second) = heap.pop().unwrap();
+		assert_eq!([first.min(second), first.max(second)], ['a', 'x']);
+		assert_eq!(heap.push(3, 'z'), Err("key too small"));
+		assert_eq!(heap.pop(), Some((9, 'b')));
+		assert_eq!(heap.pop(), Some((40, 'c')));
+		assert_eq!(heap.pop(), None);
+	}
+
+	#[test]
+	fn test_static_initialization() {
+		static HEAP: Mutex<StaticRadixHeap<u8, 8>> = Mutex::new(StaticRadixHeap::new());
+		const EMPTY: StaticRadixHeap<u8, 2> = StaticRadixHeap::new();
+
+		HEAP.lock().unwrap().push(3, b'x').unwrap();
+		assert_eq!(HEAP.lock().unwrap().pop(), Some((3, b'x')));
+		assert!(EMPTY.is_empty());
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_static_heap_random() {
+		use rand::Rng;
+
+		let mut rng = rand::thread_rng();
+		let mut heap: StaticRadixHeap<u32, 256> = StaticRadixHeap::new();
+		let mut model = crate::RadixHeap::new();
+
+		for _ in 0..10000 {
+			if rng.gen_bool(0.55) && !heap.is_full() {
+				let bits = rng.gen_range(0, 20);
+				let key = heap.bound() + rng.gen_range(0, 1 << bits);
+				heap.push(key, key).unwrap();
+				model.push(key, key).unwrap();
+			} else {
+				assert_eq!(heap.peek().map(|(k, _)| k), model.peek_key());
+				assert_eq!(heap.pop().map(|(k, _)| k), model.pop().map(|(k, _)| k));
+			}
+
+			assert_eq!(heap.len(), model.len());
+		}
+	}
+}
@@ -0,0 +1,147 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: stream.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use futures_core::Stream;
+use crate::RadixHeap;
+
+struct Shared<V: Clone + Debug + Ord> {
+	heap: RadixHeap<V>,
+	waker: Option<Waker>,
+	feeders: usize
+}
+
+// yields the items of the heap in priority order; the stream only ends
+// once the heap is empty and all feeders are gone
+pub struct PriorityStream<V: Clone + Debug + Ord> {
+	shared: Arc<Mutex<Shared<V>>>
+}
+
+// pushes items into a "PriorityStream", possibly from other tasks or threads
+pub struct PriorityFeeder<V: Clone + Debug + Ord> {
+	shared: Arc<Mutex<Shared<V>>>
+}
+
+impl<V: Clone + Debug + Ord> PriorityStream<V> {
+	pub fn new(heap: RadixHeap<V>) -> PriorityStream<V> {
+		PriorityStream { shared: Arc::new(Mutex::new(Shared { heap, waker: None, feeders: 0 })) }
+	}
+
+	pub fn feeder(&self) -> PriorityFeeder<V> {
+		self.shared.lock().unwrap().feeders += 1;
+		PriorityFeeder { shared: Arc::clone(&self.shared) }
+	}
+
+	pub fn len(&self) -> usize { self.shared.lock().unwrap().heap.len() }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+impl<V: Clone + Debug + Ord> PriorityFeeder<V> {
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> {
+		let mut shared = self.shared.lock().unwrap();
+		shared.heap.push(key, val).map_err(|_| "key too small")?;
+		if let Some(waker) = shared.waker.take() { waker.wake(); }
+		Ok(())
+	}
+}
+
+impl<V: Clone + Debug + Ord> Clone for PriorityFeeder<V> {
+	fn clone(&self) -> PriorityFeeder<V> {
+		self.shared.lock().unwrap().feeders += 1;
+		PriorityFeeder { shared: Arc::clone(&self.shared) }
+	}
+}
+
+impl<V: Clone + Debug + Ord> Drop for PriorityFeeder<V> {
+	fn drop(&mut self) {
+		let mut shared = self.shared.lock().unwrap();
+		shared.feeders -= 1;
+
+		// the stream may have to end now
+		if shared.feeders == 0 {
+			if let Some(waker) = shared.waker.take() { waker.wake(); }
+		}
+	}
+}
+
+impl<V: Clone + Debug + Ord> Stream for PriorityStream<V> {
+	type Item = (u32, V);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut shared = self.shared.lock().unwrap();
+
+		if let Some(item) = shared.heap.pop() { return Poll::Ready(Some(item)); }
+		if shared.feeders == 0 { return Poll::Ready(None); }
+
+		shared.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use futures::executor::block_on;
+	use futures::StreamExt;
+	use std::thread;
+
+	#[test]
+	fn test_priority_stream() {
+		let mut heap = RadixHeap::new();
+		heap.push(3, "c").unwrap();
+		heap.push(1, "a").unwrap();
+
+		let stream = PriorityStream::new(heap);
+		let feeder = stream.feeder();
+		assert_eq!(stream.len(), 2);
+
+		let producer = thread::spawn(move || {
+			feeder.push(7, "d").unwrap();
+			feeder.push(5, "b").unwrap();
+		});
+
+		producer.join().unwrap();
+		let items: Vec<(u32, &str)> = block_on(stream.collect());
+		assert_eq!(items, vec![(1, "a"), (3, "c"), (5, "b"), (7, "d")]);
+	}
+
+	#[test]
+	fn test_priority_stream_wakeup() {
+		let mut stream = PriorityStream::new(RadixHeap::new());
+		let feeder = stream.feeder();
+
+		let producer = thread::spawn(move || {
+			thread::sleep(std::time::Duration::from_millis(20));
+			feeder.push(2, 'x').unwrap();
+		});
+
+		assert_eq!(block_on(stream.next()), Some((2, 'x')));
+		producer.join().unwrap();
+		assert_eq!(block_on(stream.next()), None);
+	}
+}
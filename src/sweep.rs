@@ -0,0 +1,103 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: sweep.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::fmt::Debug;
+use crate::RadixHeap;
+
+// event queue of a sweep-line algorithm keyed by x-coordinate; events of
+// a batch are ordered by "E", so an enum of event kinds decides which kind
+// is handled first at a common coordinate
+#[derive(Clone, Debug)]
+pub struct SweepLine<E: Clone + Debug + Ord> {
+	heap: RadixHeap<E>,
+	position: Option<u32>
+}
+
+impl<E: Clone + Debug + Ord> SweepLine<E> {
+	pub fn new() -> SweepLine<E> { SweepLine { heap: RadixHeap::new(), position: None } }
+
+	// coordinate of the batch extracted last
+	pub fn position(&self) -> Option<u32> { self.position }
+
+	pub fn len(&self) -> usize { self.heap.len() }
+
+	pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+	pub fn insert(&mut self, x: u32, event: E) -> Result<(), &'static str> {
+		if self.position.is_some_and(|p| x < p) { return Err("event behind the sweep line"); }
+		self.heap.push(x, event).map_err(|_| "event behind the sweep line")
+	}
+
+	pub fn next_x(&self) -> Option<u32> { self.heap.peek_key() }
+
+	// moves the sweep line to the next coordinate and extracts all events
+	// located there
+	pub fn next_batch(&mut self) -> Option<(u32, Vec<E>)> {
+		let (x, first) = self.heap.pop()?;
+		let mut batch = vec![first];
+
+		while self.next_x() == Some(x) { batch.push(self.heap.pop().unwrap().1); }
+
+		batch.sort();
+		self.position = Some(x);
+		Some((x, batch))
+	}
+}
+
+impl<E: Clone + Debug + Ord> Default for SweepLine<E> {
+	fn default() -> SweepLine<E> { SweepLine::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+	enum Event {
+		Start(usize),
+		Intersection(usize, usize),
+		End(usize)
+	}
+
+	#[test]
+	fn test_sweep() {
+		let mut sweep = SweepLine::new();
+		sweep.insert(5, Event::End(0)).unwrap();
+		sweep.insert(1, Event::Start(0)).unwrap();
+		sweep.insert(5, Event::Start(1)).unwrap();
+		sweep.insert(9, Event::End(1)).unwrap();
+
+		assert_eq!(sweep.next_batch(), Some((1, vec![Event::Start(0)])));
+		assert_eq!(sweep.next_batch(), Some((5, vec![Event::Start(1), Event::End(0)])));
+		assert_eq!(sweep.position(), Some(5));
+
+		// events discovered during the sweep
+		assert!(sweep.insert(3, Event::Intersection(0, 1)).is_err());
+		sweep.insert(7, Event::Intersection(1, 2)).unwrap();
+
+		assert_eq!(sweep.next_batch(), Some((7, vec![Event::Intersection(1, 2)])));
+		assert_eq!(sweep.next_batch(), Some((9, vec![Event::End(1)])));
+		assert_eq!(sweep.next_batch(), None);
+	}
+}
@@ -0,0 +1,144 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: sync.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use crate::RadixHeap;
+
+// Pushes only lock one of several shard inboxes (picked by thread), so
+// producers rarely contend with each other. The consumer locks all shards
+// while moving their contents into its heap and popping, which is also
+// the only time the bound is raised; a push therefore never slips below
+// the bound unnoticed.
+pub struct SyncRadixHeap<V: Clone + Debug + Ord> {
+	shards: Vec<Mutex<Vec<(u32, V)>>>,
+	heap: Mutex<RadixHeap<V>>,
+	bound: AtomicU32,
+	length: AtomicUsize
+}
+
+impl<V: Clone + Debug + Ord> SyncRadixHeap<V> {
+	pub fn new(shards: usize) -> SyncRadixHeap<V> {
+		SyncRadixHeap {
+			shards: (0..shards.max(1)).map(|_| Mutex::new(Vec::new())).collect(),
+			heap: Mutex::new(RadixHeap::new()),
+			bound: AtomicU32::new(0),
+			length: AtomicUsize::new(0)
+		}
+	}
+
+	pub fn len(&self) -> usize { self.length.load(Ordering::SeqCst) }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	pub fn push(&self, key: u32, val: V) -> Result<(), &'static str> {
+		let mut shard = self.shards[thread_index(self.shards.len())].lock().unwrap();
+		if key < self.bound.load(Ordering::SeqCst) { return Err("key too small"); }
+
+		shard.push((key, val));
+		self.length.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	}
+
+	pub fn pop(&self) -> Option<(u32, V)> {
+		let mut heap = self.heap.lock().unwrap();
+		let _shards = self.collect(&mut heap);
+		let top = heap.pop();
+
+		if let Some((key, _)) = top {
+			self.bound.store(key, Ordering::SeqCst);
+			self.length.fetch_sub(1, Ordering::SeqCst);
+		}
+
+		top
+	}
+
+	pub fn peek(&self) -> Option<(u32, V)> {
+		let mut heap = self.heap.lock().unwrap();
+		let _shards = self.collect(&mut heap);
+		heap.peek()
+	}
+
+	// moves all pending items into the heap, returning the shard guards so
+	// the caller can keep producers out until the bound is updated
+	fn collect(&self, heap: &mut RadixHeap<V>) -> Vec<MutexGuard<'_, Vec<(u32, V)>>> {
+		let mut guards: Vec<_> = self.shards.iter().map(|s| s.lock().unwrap()).collect();
+
+		for shard in guards.iter_mut() {
+			for (key, val) in shard.drain(..) { heap.push(key, val).unwrap(); }
+		}
+
+		guards
+	}
+}
+
+// stable index in "0..count" for the calling thread, used to spread
+// threads over shards
+pub(crate) fn thread_index(count: usize) -> usize {
+	let mut hasher = DefaultHasher::new();
+	thread::current().id().hash(&mut hasher);
+	hasher.finish() as usize % count
+}
+
+// shareable between threads as long as the values can be sent
+#[allow(dead_code)]
+const _: () = {
+	fn send_sync<T: Send + Sync>() {}
+	fn is_send_sync<V: Clone + Debug + Ord + Send>() { send_sync::<SyncRadixHeap<V>>(); }
+};
+
+impl<V: Clone + Debug + Ord> Default for SyncRadixHeap<V> {
+	fn default() -> SyncRadixHeap<V> { SyncRadixHeap::new(8) }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::Arc;
+
+	#[test]
+	fn test_sync_heap() {
+		let heap: Arc<SyncRadixHeap<u32>> = Arc::new(SyncRadixHeap::new(4));
+
+		let producers: Vec<_> = (0..8u32).map(|p| {
+			let heap = Arc::clone(&heap);
+			thread::spawn(move || {
+				for i in 0..100u32 { heap.push(i * 8 + p, p).unwrap(); }
+			})
+		}).collect();
+
+		for producer in producers { producer.join().unwrap(); }
+		assert_eq!(heap.len(), 800);
+		assert_eq!(heap.peek(), Some((0, 0)));
+
+		let keys: Vec<u32> = (0..800).map(|_| heap.pop().unwrap().0).collect();
+		assert_eq!(keys, (0..800).collect::<Vec<u32>>());
+		assert!(heap.is_empty());
+		assert_eq!(heap.push(10, 0), Err("key too small"));
+	}
+}
@@ -0,0 +1,327 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: testing.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+use crate::MonotonePriorityQueue;
+
+// reference model of a monotone priority queue, backed by a "BTreeMap";
+// items with equal keys are popped in the order they were pushed
+#[derive(Clone, Debug)]
+pub struct Model<V> {
+	items: BTreeMap<u32, VecDeque<V>>,
+	length: usize,
+	bound: u32
+}
+
+impl<V: Clone + Debug + PartialEq> Model<V> {
+	pub fn new() -> Model<V> { Model { items: BTreeMap::new(), length: 0, bound: 0 } }
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.bound { return Err("key too small"); }
+
+		self.items.entry(key).or_default().push_back(val);
+		self.length += 1;
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let (&key, _) = self.items.iter().next()?;
+		let val = self.items.get_mut(&key).unwrap().pop_front().unwrap();
+		self.forget(key);
+		Some((key, val))
+	}
+
+	// removes an item popped by an implementation free to choose among
+	// equal keys; fails if the item does not carry the smallest key
+	pub fn remove(&mut self, key: u32, val: &V) -> Result<(), &'static str> {
+		if Some(key) != self.peek_key() { return Err("popped key is not the smallest one"); }
+
+		let items = self.items.get_mut(&key).unwrap();
+		let position = items.iter().position(|v| v == val).ok_or("popped item was never pushed")?;
+		items.remove(position);
+		self.forget(key);
+		Ok(())
+	}
+
+	pub fn peek(&self) -> Option<(u32, V)> {
+		self.items.iter().next().map(|(&k, items)| (k, items[0].clone()))
+	}
+
+	pub fn peek_key(&self) -> Option<u32> { self.items.keys().next().copied() }
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	pub fn bound(&self) -> u32 { self.bound }
+
+	// the bound is kept, just like by the heap
+	pub fn clear(&mut self) {
+		self.items.clear();
+		self.length = 0;
+	}
+
+	fn forget(&mut self, key: u32) {
+		if self.items[&key].is_empty() { self.items.remove(&key); }
+		self.length -= 1;
+		self.bound = key;
+	}
+}
+
+impl<V: Clone + Debug + PartialEq> Default for Model<V> {
+	fn default() -> Model<V> { Model::new() }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+	Push(u32),
+	Pop,
+	Peek,
+	Clear
+}
+
+// endless, reproducible sequence of operations; pushed keys never fall
+// below the bound, and lie less than "spread" above it
+#[derive(Clone, Debug)]
+pub struct Operations {
+	state: u64,
+	spread: u32,
+	model: Model<()>
+}
+
+impl Operations {
+	pub fn new(seed: u64) -> Operations {
+		Operations { state: seed | 1, spread: 1000, model: Model::new() }
+	}
+
+	// small spreads produce many equal keys
+	pub fn spread(mut self, spread: u32) -> Operations {
+		self.spread = spread.max(1);
+		self
+	}
+
+	// xorshift generator
+	fn random(&mut self) -> u64 {
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 7;
+		self.state ^= self.state << 17;
+		self.state
+	}
+}
+
+impl Iterator for Operations {
+	type Item = Op;
+
+	fn next(&mut self) -> Option<Op> {
+		let roll = self.random() % 100;
+
+		let op = if roll < 55 {
+			let key = self.model.bound().saturating_add((self.random() % self.spread as u64) as u32);
+			self.model.push(key, ()).unwrap();
+			Op::Push(key)
+		} else if roll < 90 {
+			self.model.pop();
+			Op::Pop
+		} else if roll < 99 { Op::Peek } else {
+			self.model.clear();
+			Op::Clear
+		};
+
+		Some(op)
+	}
+}
+
+// how "ScenarioGenerator" picks the keys it pushes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+	// keys spread evenly over less than "spread" above the bound
+	Uniform { spread: u32 },
+	// keys are arrival times whose gaps follow an exponential distribution
+	// of the given mean, as for events arriving independently of each other
+	Exponential { mean: f64 },
+	// arrivals come in bursts of "size" keys at most "jitter" apart, the
+	// bursts being "gap" keys apart
+	Bursty { size: u32, jitter: u32, gap: u32 }
+}
+
+// endless, reproducible stream of pushes and pops for benchmarking code
+// built on a monotone queue; pops are only issued while items are left
+#[derive(Clone, Debug)]
+pub struct ScenarioGenerator {
+	random: Operations,
+	distribution: Distribution,
+	// share of pushes in per mille
+	pushes: u64,
+	// last arrival time, and the number of keys left in the current burst
+	clock: u32,
+	burst: u32
+}
+
+impl ScenarioGenerator {
+	pub fn new(seed: u64, distribution: Distribution) -> ScenarioGenerator {
+		ScenarioGenerator { random: Operations::new(seed), distribution, pushes: 550, clock: 0, burst: 0 }
+	}
+
+	// share of pushes among all operations, from 0 to 1
+	pub fn push_ratio(mut self, ratio: f64) -> ScenarioGenerator {
+		self.pushes = (ratio.clamp(0.0, 1.0) * 1000.0) as u64;
+		self
+	}
+
+	fn key(&mut self) -> u32 {
+		let bound = self.random.model.bound();
+
+		match self.distribution {
+			Distribution::Uniform { spread } => bound.saturating_add((self.random.random() % spread.max(1) as u64) as u32),
+			Distribution::Exponential { mean } => {
+				let uniform = (self.random.random() >> 11) as f64 / (1u64 << 53) as f64;
+				self.clock = self.clock.saturating_add((-mean * (1.0 - uniform).ln()).round() as u32);
+				self.clock.max(bound)
+			},
+			Distribution::Bursty { size, jitter, gap } => {
+				if self.burst == 0 {
+					self.clock = self.clock.saturating_add(gap);
+					self.burst = size.max(1);
+				}
+
+				self.burst -= 1;
+				let offset = (self.random.random() % (jitter as u64 + 1)) as u32;
+				self.clock.saturating_add(offset).max(bound)
+			}
+		}
+	}
+}
+
+impl Iterator for ScenarioGenerator {
+	type Item = Op;
+
+	fn next(&mut self) -> Option<Op> {
+		if self.random.model.is_empty() || self.random.random() % 1000 < self.pushes {
+			let key = self.key();
+			self.random.model.push(key, ()).unwrap();
+			Some(Op::Push(key))
+		} else {
+			self.random.model.pop();
+			Some(Op::Pop)
+		}
+	}
+}
+
+// applies the operations to a queue holding the number of every push as
+// its value, and compares every result against the model
+pub fn check<Q, I>(queue: &mut Q, ops: I) -> Result<(), String>
+	where Q: MonotonePriorityQueue<u64>, I: IntoIterator<Item = Op> {
+	let mut model = Model::new();
+
+	for (step, op) in ops.into_iter().enumerate() {
+		let fail = |e: &str| format!("step {} ({:?}): {}", step, op, e);
+
+		match op {
+			Op::Push(key) => {
+				if queue.push(key, step as u64) != model.push(key, step as u64) {
+					return Err(fail("push result differs from the model"));
+				}
+			},
+			Op::Pop => match queue.pop() {
+				Some((key, val)) => model.remove(key, &val).map_err(fail)?,
+				None if !model.is_empty() => return Err(fail("queue ran empty too early")),
+				None => {}
+			},
+			Op::Peek => {
+				if queue.peek().map(|(k, _)| k) != model.peek_key() { return Err(fail("peeked key differs")); }
+			},
+			Op::Clear => {
+				queue.clear();
+				model.clear();
+			}
+		}
+
+		if queue.len() != model.len() { return Err(fail("length differs from the model")); }
+		if queue.bound() != model.bound() { return Err(fail("bound differs from the model")); }
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::RadixHeap;
+	use crate::ladder::LadderQueue;
+
+	#[test]
+	fn test_model() {
+		let mut model = Model::new();
+		model.push(4, 'a').unwrap();
+		model.push(2, 'b').unwrap();
+		model.push(2, 'c').unwrap();
+
+		assert_eq!(model.pop(), Some((2, 'b')));
+		assert_eq!(model.push(1, 'd'), Err("key too small"));
+		assert_eq!(model.remove(4, &'a'), Err("popped key is not the smallest one"));
+		assert_eq!(model.remove(2, &'c'), Ok(()));
+		assert_eq!(model.peek(), Some((4, 'a')));
+		assert_eq!(model.len(), 1);
+	}
+
+	#[test]
+	fn test_check() {
+		for spread in [1, 100, 1 << 20] {
+			let ops: Vec<Op> = Operations::new(7).spread(spread).take(5000).collect();
+			assert_eq!(check(&mut RadixHeap::new(), ops.iter().copied()), Ok(()));
+			assert_eq!(check(&mut LadderQueue::new(), ops.iter().copied()), Ok(()));
+		}
+
+		// a queue breaking the contract is caught
+		let mut heap = RadixHeap::new();
+		heap.push(0, u64::MAX).unwrap();
+		assert!(check(&mut heap, Operations::new(1).take(100)).is_err());
+	}
+
+	#[test]
+	fn test_scenarios() {
+		let distributions = [
+			Distribution::Uniform { spread: 500 },
+			Distribution::Exponential { mean: 20.0 },
+			Distribution::Bursty { size: 50, jitter: 2, gap: 1000 }
+		];
+
+		for distribution in distributions {
+			let ops: Vec<Op> = ScenarioGenerator::new(3, distribution).take(5000).collect();
+			assert_eq!(ops, ScenarioGenerator::new(3, distribution).take(5000).collect::<Vec<Op>>());
+			assert_eq!(check(&mut RadixHeap::new(), ops.iter().copied()), Ok(()));
+			assert!(ops.iter().all(|&op| op != Op::Peek && op != Op::Clear));
+		}
+
+		let keys: Vec<u32> = ScenarioGenerator::new(9, Distribution::Exponential { mean: 20.0 }).push_ratio(1.0)
+			.take(10000).map(|op| if let Op::Push(key) = op { key } else { unreachable!() }).collect();
+		assert!((180_000..220_000).contains(keys.last().unwrap()));
+
+		let bursts = ScenarioGenerator::new(9, Distribution::Bursty { size: 100, jitter: 0, gap: 10 }).push_ratio(1.0);
+		let mut keys: Vec<Op> = bursts.take(1000).collect();
+		keys.dedup();
+		assert_eq!(keys.len(), 10);
+	}
+}
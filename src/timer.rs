@@ -0,0 +1,298 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: timer.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use crate::clock::{Clock, ManualClock};
+use crate::RadixHeap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(u64);
+
+// when a repeating timer fires next: "FixedRate" keeps to the original
+// schedule, firing once for all periods missed in between, "FixedDelay"
+// counts the interval from the time the timer actually fired. A timer
+// whose next deadline lies beyond "u32::MAX" fires a last time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+	FixedRate,
+	FixedDelay
+}
+
+// schedule of a repeating timer, shared with "EventQueue" and "Scheduler"
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Period<T> {
+	interval: u32,
+	repeat: Repeat,
+	// every firing hands out a copy of the payload
+	pub(crate) copy: fn(&T) -> T
+}
+
+impl<T: Clone> Period<T> {
+	pub(crate) fn new(interval: u32, repeat: Repeat) -> Period<T> {
+		assert!(interval > 0, "repeat interval must not be zero");
+		Period { interval, repeat, copy: T::clone }
+	}
+}
+
+impl<T> Period<T> {
+	// deadline following "deadline" once fired at "now", if any is left
+	pub(crate) fn next(&self, deadline: u32, now: u32) -> Option<u32> {
+		let (deadline, interval, now) = (deadline as u64, self.interval as u64, now.max(deadline) as u64);
+		let next = match self.repeat {
+			Repeat::FixedRate => deadline + ((now - deadline) / interval + 1) * interval,
+			Repeat::FixedDelay => now + interval
+		};
+
+		u32::try_from(next).ok()
+	}
+}
+
+// the buckets of the radix heap act as the levels of a hierarchical timer
+// wheel: far deadlines sit in coarse buckets and are only redistributed
+// into finer ones as time approaches them
+#[derive(Clone, Debug)]
+pub struct TimerWheel<T, C: Clock = ManualClock> {
+	heap: RadixHeap<TimerId>,
+	// cancelled timers are only removed here, their heap entries are
+	// skipped once they come up
+	timers: HashMap<TimerId, (u32, T)>,
+	periods: HashMap<TimerId, Period<T>>,
+	next: u64,
+	now: u32,
+	clock: C
+}
+
+impl<T> TimerWheel<T> {
+	pub fn new() -> TimerWheel<T> { TimerWheel::with_clock(ManualClock::new()) }
+}
+
+impl<T, C: Clock> TimerWheel<T, C> {
+	// the wheel starts at the current time of "clock"
+	pub fn with_clock(clock: C) -> TimerWheel<T, C> {
+		let now = clock.now();
+		TimerWheel { heap: RadixHeap::new(), timers: HashMap::new(), periods: HashMap::new(), next: 0, now, clock }
+	}
+
+	pub fn clock(&self) -> &C { &self.clock }
+
+	pub fn now(&self) -> u32 { self.now }
+
+	pub fn len(&self) -> usize { self.timers.len() }
+
+	pub fn is_empty(&self) -> bool { self.timers.is_empty() }
+
+	pub fn contains(&self, id: TimerId) -> bool { self.timers.contains_key(&id) }
+
+	pub fn deadline(&self, id: TimerId) -> Option<u32> { self.timers.get(&id).map(|&(d, _)| d) }
+
+	// earliest time at which "advance" may return expired timers; this can
+	// be the deadline of an already cancelled timer
+	pub fn next_deadline(&self) -> Option<u32> {
+		if self.is_empty() { None } else { self.heap.peek_key() }
+	}
+
+	// deadlines already passed expire on the next call to "advance"
+	pub fn insert(&mut self, deadline: u32, payload: T) -> TimerId {
+		let id = TimerId(self.next);
+		self.next += 1;
+
+		self.heap.push(deadline.max(self.now), id).unwrap();
+		self.timers.insert(id, (deadline, payload));
+		id
+	}
+
+	pub fn cancel(&mut self, id: TimerId) -> Option<T> {
+		self.periods.remove(&id);
+		let payload = self.timers.remove(&id).map(|(_, p)| p);
+		if payload.is_some() { self.compact(); }
+		payload
+	}
+
+	// moves the clock forward and returns all timers that expired in the
+	// meantime, ordered by their deadline
+	pub fn advance(&mut self, now: u32) -> Vec<(TimerId, T)> {
+		let mut expired = Vec::new();
+		if now > self.now { self.now = now; }
+
+		while let Some(deadline) = self.heap.peek_key() {
+			if deadline > self.now { break; }
+
+			let (_, id) = self.heap.pop().unwrap();
+
+			if let Some(period) = self.periods.get(&id) {
+				let timer = self.timers.get_mut(&id).unwrap();
+
+				if let Some(next) = period.next(timer.0, self.now) {
+					timer.0 = next;
+					self.heap.push(next, id).unwrap();
+					expired.push((id, (period.copy)(&timer.1)));
+					continue;
+				}
+
+				self.periods.remove(&id);
+			}
+
+			if let Some((_, payload)) = self.timers.remove(&id) { expired.push((id, payload)); }
+		}
+
+		expired
+	}
+
+	// advances to the current time of the clock
+	pub fn tick(&mut self) -> Vec<(TimerId, T)> {
+		let now = self.clock.now();
+		self.advance(now)
+	}
+
+	pub fn clear(&mut self) {
+		self.heap.clear();
+		self.timers.clear();
+		self.periods.clear();
+	}
+
+	// rebuilds the heap once cancelled entries outnumber the live ones
+	fn compact(&mut self) {
+		if self.heap.len() <= 2 * self.timers.len() + 32 { return; }
+
+		let now = self.now;
+		self.heap = RadixHeap::new();
+
+		for (&id, &(deadline, _)) in &self.timers {
+			self.heap.push(deadline.max(now), id).unwrap();
+		}
+	}
+}
+
+impl<T: Clone, C: Clock> TimerWheel<T, C> {
+	// fires every "interval" ticks, first at "now + interval", until the
+	// timer is cancelled
+	pub fn insert_repeating(&mut self, interval: u32, repeat: Repeat, payload: T) -> TimerId {
+		let period = Period::new(interval, repeat);
+		let id = self.insert(self.now.saturating_add(interval), payload);
+		self.periods.insert(id, period);
+		id
+	}
+}
+
+impl<T> Default for TimerWheel<T> {
+	fn default() -> TimerWheel<T> { TimerWheel::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::clock::StepClock;
+
+	#[test]
+	fn test_timers() {
+		let mut timers = TimerWheel::new();
+		let a = timers.insert(100, "a");
+		let b = timers.insert(50, "b");
+		let c = timers.insert(70, "c");
+
+		assert_eq!(timers.next_deadline(), Some(50));
+		assert_eq!(timers.cancel(c), Some("c"));
+		assert_eq!(timers.cancel(c), None);
+		assert_eq!(timers.advance(20), vec![]);
+		assert_eq!(timers.advance(80), vec![(b, "b")]);
+
+		// already expired deadlines fire on the next advance
+		let d = timers.insert(10, "d");
+		assert_eq!(timers.deadline(d), Some(10));
+		assert_eq!(timers.advance(80), vec![(d, "d")]);
+		assert_eq!(timers.advance(100), vec![(a, "a")]);
+		assert!(timers.is_empty());
+	}
+
+	#[test]
+	fn test_compaction() {
+		let mut timers = TimerWheel::new();
+		let ids: Vec<TimerId> = (0..1000).map(|i| timers.insert(i, i)).collect();
+
+		for id in &ids[..990] { timers.cancel(*id); }
+
+		assert!(timers.heap.len() < 100);
+		assert_eq!(timers.advance(u32::MAX).into_iter().map(|(_, p)| p).collect::<Vec<u32>>(),
+		           (990..1000).collect::<Vec<u32>>());
+	}
+
+	#[test]
+	fn test_repeating() {
+		let mut timers = TimerWheel::new();
+		let rate = timers.insert_repeating(10, Repeat::FixedRate, "rate");
+		let delay = timers.insert_repeating(10, Repeat::FixedDelay, "delay");
+
+		assert_eq!(timers.advance(10).len(), 2);
+
+		// fired late at 25: the fixed rate timer catches up on 20, the
+		// fixed delay one moves on to 35
+		assert_eq!(timers.advance(25), vec![(rate, "rate"), (delay, "delay")]);
+		assert_eq!(timers.deadline(rate), Some(30));
+		assert_eq!(timers.deadline(delay), Some(35));
+
+		assert_eq!(timers.cancel(rate), Some("rate"));
+		assert_eq!(timers.advance(40), vec![(delay, "delay")]);
+		assert_eq!(timers.len(), 1);
+
+		// missed periods fire only once
+		let rate = timers.insert_repeating(10, Repeat::FixedRate, "rate");
+		assert_eq!(timers.advance(100_000), vec![(delay, "delay"), (rate, "rate")]);
+		assert_eq!(timers.deadline(rate), Some(100_010));
+	}
+
+	#[test]
+	fn test_repeating_at_end_of_time() {
+		let mut timers = TimerWheel::new();
+		let rate = timers.insert_repeating(7, Repeat::FixedRate, "rate");
+		let delay = timers.insert_repeating(7, Repeat::FixedDelay, "delay");
+		timers.advance(u32::MAX - 10);
+
+		// both fire a last time, as no later deadline is left
+		assert_eq!(timers.advance(u32::MAX), vec![(rate, "rate"), (delay, "delay")]);
+		assert!(timers.is_empty() && timers.advance(u32::MAX).is_empty());
+
+		let mut clocked = TimerWheel::with_clock(StepClock::new(u32::MAX - 1, 1));
+		clocked.insert_repeating(1, Repeat::FixedDelay, ());
+		assert_eq!((clocked.tick().len(), clocked.tick().len()), (1, 0));
+		assert!(clocked.is_empty());
+	}
+
+	#[test]
+	fn test_clock() {
+		let clock = ManualClock::new();
+		let mut timers = TimerWheel::with_clock(clock.clone());
+		let a = timers.insert(10, "a");
+		let b = timers.insert(30, "b");
+
+		clock.advance(15);
+		assert_eq!(timers.tick(), vec![(a, "a")]);
+		assert_eq!(timers.now(), 15);
+
+		let mut stepped = TimerWheel::with_clock(StepClock::new(0, 10));
+		stepped.insert(15, "c");
+		assert_eq!((stepped.tick().len(), stepped.tick().len()), (0, 1));
+		assert!(timers.contains(b));
+	}
+}
@@ -0,0 +1,198 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: tombstone.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use crate::RadixHeap;
+
+// index of a slot plus the generation the slot had when the handle was
+// handed out; slots are reused, so handles of removed items never match
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TombstoneHandle {
+	index: u32,
+	generation: u32
+}
+
+#[derive(Clone, Debug)]
+struct Slot<V> {
+	generation: u32,
+	val: Option<V>
+}
+
+// radix heap with removal in constant time: "remove()" only empties the
+// slot of the value, its heap entry stays behind as a tombstone until it
+// comes up or the heap gets compacted
+#[derive(Clone, Debug)]
+pub struct TombstoneHeap<V> {
+	heap: RadixHeap<TombstoneHandle>,
+	slots: Vec<Slot<V>>,
+	free: Vec<u32>,
+	live: usize
+}
+
+impl<V> TombstoneHeap<V> {
+	pub fn new() -> TombstoneHeap<V> {
+		TombstoneHeap { heap: RadixHeap::new(), slots: Vec::new(), free: Vec::new(), live: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.live }
+
+	pub fn is_empty(&self) -> bool { self.live == 0 }
+
+	// number of removed entries still held by the heap
+	pub fn tombstones(&self) -> usize { self.heap.len() - self.live }
+
+	pub fn bound(&self) -> u32 { self.heap.bound() }
+
+	pub fn contains(&self, handle: TombstoneHandle) -> bool { self.get(handle).is_some() }
+
+	pub fn get(&self, handle: TombstoneHandle) -> Option<&V> {
+		let slot = self.slots.get(handle.index as usize)?;
+		if slot.generation == handle.generation { slot.val.as_ref() } else { None }
+	}
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<TombstoneHandle, &'static str> {
+		if key < self.heap.bound() { return Err("key too small"); }
+
+		let index = self.free.pop().unwrap_or_else(|| {
+			self.slots.push(Slot { generation: 0, val: None });
+			(self.slots.len() - 1) as u32
+		});
+
+		let slot = &mut self.slots[index as usize];
+		slot.val = Some(val);
+		let handle = TombstoneHandle { index, generation: slot.generation };
+
+		self.heap.push(key, handle)?;
+		self.live += 1;
+		Ok(handle)
+	}
+
+	pub fn remove(&mut self, handle: TombstoneHandle) -> Option<V> {
+		let val = self.take(handle)?;
+		if self.tombstones() > self.live + 32 { self.compact(); }
+		Some(val)
+	}
+
+	pub fn peek(&mut self) -> Option<(u32, &V)> {
+		self.skip_tombstones();
+		let key = self.heap.peek_key()?;
+		self.get(*self.heap.peek_value()?).map(|val| (key, val))
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		self.skip_tombstones();
+		let (key, handle) = self.heap.pop()?;
+		Some((key, self.take(handle).unwrap()))
+	}
+
+	pub fn clear(&mut self) {
+		let handles: Vec<TombstoneHandle> = self.heap.tuples().into_iter().map(|(_, h)| h).collect();
+		for handle in handles { self.take(handle); }
+		self.heap.clear();
+	}
+
+	// rebuilds the heap from the live entries only; the bound is kept
+	pub fn compact(&mut self) {
+		let mut live = self.heap.tuples();
+		live.retain(|&(_, handle)| self.contains(handle));
+
+		self.heap.clear();
+		for (key, handle) in live { self.heap.push(key, handle).unwrap(); }
+	}
+
+	// empties the slot and retires its generation, so the handle goes stale
+	fn take(&mut self, handle: TombstoneHandle) -> Option<V> {
+		let slot = self.slots.get_mut(handle.index as usize).filter(|s| s.generation == handle.generation)?;
+		let val = slot.val.take()?;
+
+		slot.generation = slot.generation.wrapping_add(1);
+		self.free.push(handle.index);
+		self.live -= 1;
+		Some(val)
+	}
+
+	fn skip_tombstones(&mut self) {
+		while let Some(handle) = self.heap.peek_value() {
+			if self.contains(*handle) { break; }
+			self.heap.pop();
+		}
+	}
+}
+
+impl<V> Default for TombstoneHeap<V> {
+	fn default() -> TombstoneHeap<V> { TombstoneHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_tombstones() {
+		let mut heap = TombstoneHeap::new();
+		let a = heap.push(10, "a").unwrap();
+		let b = heap.push(20, "b").unwrap();
+		heap.push(30, "c").unwrap();
+
+		assert_eq!(heap.remove(a), Some("a"));
+		assert_eq!(heap.remove(a), None);
+		assert!(!heap.contains(a) && heap.contains(b));
+		assert_eq!((heap.len(), heap.tombstones()), (2, 1));
+
+		assert_eq!(heap.peek(), Some((20, &"b")));
+		assert_eq!(heap.tombstones(), 0);
+		assert_eq!(heap.pop(), Some((20, "b")));
+		assert_eq!(heap.push(15, "x"), Err("key too small"));
+		assert_eq!(heap.pop(), Some((30, "c")));
+		assert_eq!(heap.pop(), None);
+	}
+
+	#[test]
+	fn test_stale_handles() {
+		let mut heap = TombstoneHeap::new();
+		let old = heap.push(10, "old").unwrap();
+		assert_eq!(heap.pop(), Some((10, "old")));
+
+		// the slot is reused, but the old handle does not match it
+		let new = heap.push(20, "new").unwrap();
+		assert_eq!(heap.slots.len(), 1);
+		assert!(!heap.contains(old));
+		assert_eq!(heap.remove(old), None);
+		assert_eq!(heap.get(new), Some(&"new"));
+	}
+
+	#[test]
+	fn test_compaction() {
+		let mut heap = TombstoneHeap::new();
+		heap.push(5, 0).unwrap();
+		heap.pop();
+
+		let handles: Vec<TombstoneHandle> = (0..1000).map(|i| heap.push(100 + i, i).unwrap()).collect();
+		for handle in &handles[..900] { heap.remove(*handle); }
+
+		assert!(heap.tombstones() <= heap.len() + 32);
+		assert!(heap.slots.len() <= 1000);
+		assert_eq!(heap.bound(), 5);
+		assert_eq!(heap.pop(), Some((1000, 900)));
+	}
+}
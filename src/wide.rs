@@ -0,0 +1,173 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: wide.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::mem;
+
+// radix heap with digits of "BITS" bits instead of single bits: keys are
+// put into a bucket by the highest digit they differ from the bound in and
+// the value of that digit, giving "2^BITS" buckets per digit. Items are
+// redistributed at most once per digit instead of once per bit, at the
+// cost of more buckets to scan. "BITS" ranges from 1 to 16.
+#[derive(Clone, Debug)]
+pub struct WideRadixHeap<V, const BITS: u32> {
+	buckets: Vec<Vec<(u32, V)>>,
+	toplast: u32,
+	length: usize
+}
+
+// base 16 and base 256 digits
+pub type Radix16Heap<V> = WideRadixHeap<V, 4>;
+pub type Radix256Heap<V> = WideRadixHeap<V, 8>;
+
+impl<V, const BITS: u32> WideRadixHeap<V, BITS> {
+	const RADIX: usize = 1 << BITS;
+	const LEVELS: usize = 32_usize.div_ceil(BITS as usize);
+
+	pub fn new() -> WideRadixHeap<V, BITS> {
+		const { assert!(BITS >= 1 && BITS <= 16, "digit width must be between 1 and 16 bits") };
+
+		let count = 1 + Self::LEVELS * Self::RADIX;
+		WideRadixHeap { buckets: (0..count).map(|_| Vec::new()).collect(), toplast: 0, length: 0 }
+	}
+
+	pub fn len(&self) -> usize { self.length }
+
+	pub fn is_empty(&self) -> bool { self.length == 0 }
+
+	pub fn bound(&self) -> u32 { self.toplast }
+
+	pub fn bucket_count(&self) -> usize { self.buckets.len() }
+
+	// bucket 0 holds keys equal to the bound, the others follow by digit
+	// position and then by digit value, so keys grow with the index
+	fn bucket_index(&self, key: u32) -> usize {
+		if key == self.toplast { return 0; }
+
+		let level = (31 - (key ^ self.toplast).leading_zeros()) / BITS;
+		let digit = (key >> (level * BITS)) as usize & (Self::RADIX - 1);
+		1 + level as usize * Self::RADIX + digit
+	}
+
+	pub fn push(&mut self, key: u32, val: V) -> Result<(), &'static str> {
+		if key < self.toplast { return Err("key too small"); }
+
+		let index = self.bucket_index(key);
+		self.buckets[index].push((key, val));
+		self.length += 1;
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> Option<(u32, V)> {
+		let index = self.buckets.iter().position(|b| !b.is_empty())?;
+		self.length -= 1;
+
+		// all keys of a bucket of the lowest digit are equal
+		if index <= Self::RADIX {
+			let top = self.buckets[index].pop();
+			if let Some((key, _)) = top { self.toplast = key; }
+			return top;
+		}
+
+		// the smallest key becomes the bound; all other items of the bucket
+		// move to buckets of lower digits
+		let mut items = mem::take(&mut self.buckets[index]);
+		self.toplast = items.iter().map(|&(k, _)| k).min().unwrap();
+
+		for (key, val) in items.drain(..) {
+			let to = self.bucket_index(key);
+			self.buckets[to].push((key, val));
+		}
+
+		self.buckets[index] = items;
+		self.buckets[0].pop()
+	}
+
+	pub fn clear(&mut self) {
+		for bucket in &mut self.buckets { bucket.clear(); }
+		self.length = 0;
+	}
+}
+
+impl<V: Clone, const BITS: u32> WideRadixHeap<V, BITS> {
+	pub fn peek(&self) -> Option<(u32, V)> {
+		let bucket = self.buckets.iter().find(|b| !b.is_empty())?;
+		bucket.iter().min_by_key(|(k, _)| *k).cloned()
+	}
+}
+
+impl<V, const BITS: u32> Default for WideRadixHeap<V, BITS> {
+	fn default() -> WideRadixHeap<V, BITS> { WideRadixHeap::new() }
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use rand::Rng;
+
+	fn check<const BITS: u32>(keys: &[u32]) {
+		let mut heap: WideRadixHeap<usize, BITS> = WideRadixHeap::new();
+		for (i, &key) in keys.iter().enumerate() { heap.push(key, i).unwrap(); }
+
+		let mut sorted = keys.to_vec();
+		sorted.sort_unstable();
+
+		let popped: Vec<u32> = std::iter::from_fn(|| heap.pop().map(|(k, _)| k)).collect();
+		assert_eq!(popped, sorted);
+		assert!(heap.is_empty());
+	}
+
+	#[test]
+	fn test_wide_heap() {
+		let mut rng = rand::thread_rng();
+		let keys: Vec<u32> = (0..2000).map(|_| rng.gen()).collect();
+		let small: Vec<u32> = (0..2000).map(|_| rng.gen_range(0, 300)).collect();
+
+		for keys in [&keys, &small] {
+			check::<1>(keys);
+			check::<4>(keys);
+			check::<8>(keys);
+			check::<11>(keys);
+		}
+
+		check::<8>(&[u32::MAX, 0, u32::MAX, 1 << 31]);
+		assert_eq!(Radix256Heap::<()>::new().bucket_count(), 1 + 4 * 256);
+	}
+
+	#[test]
+	fn test_interleaved() {
+		let mut heap: Radix16Heap<char> = Radix16Heap::new();
+		heap.push(0x35, 'b').unwrap();
+		heap.push(0x1f0, 'c').unwrap();
+		heap.push(0x30, 'a').unwrap();
+
+		assert_eq!(heap.pop(), Some((0x30, 'a')));
+		assert_eq!(heap.push(0x2f, 'x'), Err("key too small"));
+		heap.push(0x30, 'd').unwrap();
+		assert_eq!(heap.peek(), Some((0x30, 'd')));
+		assert_eq!(heap.pop(), Some((0x30, 'd')));
+		assert_eq!(heap.pop(), Some((0x35, 'b')));
+		assert_eq!(heap.pop(), Some((0x1f0, 'c')));
+		assert_eq!(heap.pop(), None);
+	}
+}
@@ -0,0 +1,119 @@
+/*
+ * radixheap - Radix heap data structure library
+ * Copyright (C) 2019, 2020 Daniel Haase
+ *
+ * File: yen.rs
+ * Author: Daniel Haase
+ *
+ * This file is part of radixheap.
+ *
+ * radixheap is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * radixheap is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with radixheap.
+ * If not, see <https://www.gnu.org/licenses/lgpl-3.0.txt>.
+ */
+
+use std::collections::HashSet;
+use crate::dijkstra::shortest_path;
+use crate::RadixHeap;
+
+// the "k" shortest loopless paths from "source" to "target" by Yen's
+// algorithm, as "(distance, nodes)" in ascending order of distance; fewer
+// are returned if the graph does not have that many
+pub fn k_shortest_paths(graph: &[Vec<(usize, u32)>], source: usize, target: usize, k: usize)
+	-> Vec<(u32, Vec<usize>)> {
+	let mut found: Vec<(u32, Vec<usize>)> = Vec::new();
+	if k == 0 { return found; }
+
+	// candidates never cost less than the path accepted last, so their
+	// keys suit the radix heap
+	let mut candidates: RadixHeap<Vec<usize>> = RadixHeap::new();
+	let mut seen: HashSet<Vec<usize>> = HashSet::new();
+
+	match shortest_path(graph, source, target) {
+		Some(first) => {
+			seen.insert(first.1.clone());
+			found.push(first);
+		},
+		None => return found
+	}
+
+	while found.len() < k {
+		let last = found.last().unwrap().1.clone();
+
+		for spur in 0..last.len() - 1 {
+			let root = &last[..=spur];
+
+			// edges leaving the spur node on paths sharing this root
+			let removed: HashSet<(usize, usize)> = found.iter()
+				.filter(|(_, p)| p.len() > spur + 1 && p[..=spur] == *root)
+				.map(|(_, p)| (p[spur], p[spur + 1]))
+				.collect();
+
+			let masked = mask(graph, &root[..spur], &removed);
+			let Some((spur_cost, spur_path)) = shortest_path(&masked, last[spur], target) else { continue; };
+			let Some(cost) = path_cost(graph, root).and_then(|c| c.checked_add(spur_cost)) else { continue; };
+
+			let path: Vec<usize> = root[..spur].iter().copied().chain(spur_path).collect();
+			if seen.insert(path.clone()) { candidates.push(cost, path).unwrap(); }
+		}
+
+		match candidates.pop() {
+			Some(next) => found.push(next),
+			None => break
+		}
+	}
+
+	found
+}
+
+// copy of "graph" without the edges leaving or entering "nodes" and
+// without the edges in "edges"
+fn mask(graph: &[Vec<(usize, u32)>], nodes: &[usize], edges: &HashSet<(usize, usize)>) -> Vec<Vec<(usize, u32)>> {
+	let mut blocked = vec![false; graph.len()];
+	for &node in nodes { blocked[node] = true; }
+
+	graph.iter().enumerate().map(|(u, out)| {
+		if blocked[u] { return Vec::new(); }
+		out.iter().copied().filter(|&(v, _)| !blocked[v] && !edges.contains(&(u, v))).collect()
+	}).collect()
+}
+
+// parallel edges count with their lowest weight, as in the search
+fn path_cost(graph: &[Vec<(usize, u32)>], path: &[usize]) -> Option<u32> {
+	path.windows(2).try_fold(0u32, |cost, pair| {
+		let weight = graph[pair[0]].iter().filter(|&&(v, _)| v == pair[1]).map(|&(_, w)| w).min()?;
+		cost.checked_add(weight)
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_k_shortest_paths() {
+		let (c, d, e, f, g, h) = (0, 1, 2, 3, 4, 5);
+		let mut graph = vec![Vec::new(); 6];
+		for &(u, v, w) in &[(c, d, 3), (c, e, 2), (d, f, 4), (e, d, 1), (e, f, 2), (e, g, 3), (f, g, 2), (f, h, 1), (g, h, 2)] {
+			graph[u].push((v, w));
+		}
+
+		let paths = k_shortest_paths(&graph, c, h, 10);
+		assert_eq!(paths[0], (5, vec![c, e, f, h]));
+		assert_eq!(paths[1], (7, vec![c, e, g, h]));
+		assert_eq!(paths.iter().map(|&(cost, _)| cost).collect::<Vec<u32>>(), vec![5, 7, 8, 8, 8, 11, 11]);
+
+		assert!(k_shortest_paths(&graph, h, c, 3).is_empty());
+		assert_eq!(k_shortest_paths(&graph, c, h, 1).len(), 1);
+	}
+}